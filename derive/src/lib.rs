@@ -0,0 +1,285 @@
+//! `custom-utils`的派生宏，通过`custom-utils`的feature `derive`使用
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Expr, ExprLit, Fields, GenericArgument,
+    Lit, LitStr, PathArguments, Result, Type,
+};
+
+/// 为结构体实现`custom_utils::args::CustomArgs`。
+///
+/// 结构体属性`#[args(name = "..", version = "..")]`，默认取包名与包版本；
+/// 结构体的文档注释作为`about`。
+///
+/// 字段属性`#[arg(..)]`：
+///     `long = "name"`，默认为字段名（`_`换为`-`）
+///     `short`或`short = 'c'`，前者取字段名首字母
+///     `value_name = "PATH"`、`default = "53"`、`path`、`hidden`
+/// 字段的文档注释作为帮助信息
+#[proc_macro_derive(CustomArgs, attributes(args, arg))]
+pub fn derive_custom_args(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+enum Kind {
+    Flag,
+    Optional(Type),
+    Multiple(Type),
+    Required(Type),
+}
+
+struct Field {
+    ident: syn::Ident,
+    kind: Kind,
+    long: String,
+    short: Option<char>,
+    value_name: String,
+    default: Option<String>,
+    path: bool,
+    hidden: bool,
+    help: String,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(name, "CustomArgs only supports structs"));
+    };
+    let Fields::Named(named) = &data.fields else {
+        return Err(Error::new_spanned(
+            name,
+            "CustomArgs only supports structs with named fields",
+        ));
+    };
+    let mut cli_name = quote!(env!("CARGO_PKG_NAME"));
+    let mut version = quote!(env!("CARGO_PKG_VERSION"));
+    for attr in input.attrs.iter().filter(|x| x.path().is_ident("args")) {
+        attr.parse_nested_meta(|meta| {
+            let value: LitStr = meta.value()?.parse()?;
+            if meta.path.is_ident("name") {
+                cli_name = quote!(#value);
+            } else if meta.path.is_ident("version") {
+                version = quote!(#value);
+            } else {
+                return Err(meta.error("expected `name` or `version`"));
+            }
+            Ok(())
+        })?;
+    }
+    let about = doc(&input.attrs);
+    let about = (!about.is_empty()).then(|| quote!(.about(#about)));
+
+    let fields = named
+        .named
+        .iter()
+        .map(parse_field)
+        .collect::<Result<Vec<_>>>()?;
+    let opts = fields.iter().map(opt_tokens);
+    let reads = fields.iter().map(read_tokens);
+    let values = fields.iter().map(|field| {
+        let ident = &field.ident;
+        match field.kind {
+            Kind::Required(_) => quote!(#ident: #ident.unwrap()),
+            _ => quote!(#ident),
+        }
+    });
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::custom_utils::args::CustomArgs for #name #ty_generics #where_clause {
+            fn cli() -> ::custom_utils::args::Cli {
+                ::custom_utils::args::Cli::new(#cli_name)
+                    .version(#version)
+                    #about
+                    #(.opt(#opts))*
+            }
+            fn from_matches(
+                matches: &::custom_utils::args::Matches,
+            ) -> ::std::result::Result<Self, ::custom_utils::args::ArgErrors> {
+                let mut errors = ::std::vec::Vec::new();
+                #(#reads)*
+                if !errors.is_empty() {
+                    return Err(::custom_utils::args::ArgErrors(errors));
+                }
+                Ok(Self { #(#values),* })
+            }
+        }
+    })
+}
+
+/// 文档注释，多行以空格连接
+fn doc(attrs: &[Attribute]) -> String {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|x| x.path().is_ident("doc"))
+        .filter_map(|x| match &x.meta.require_name_value().ok()?.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(text),
+                ..
+            }) => Some(text.value().trim().to_string()),
+            _ => None,
+        })
+        .filter(|x| !x.is_empty())
+        .collect();
+    lines.join(" ")
+}
+
+/// `Option<T>`、`Vec<T>`中的`T`
+fn inner_type(ty: &Type, wrapper: &str) -> Option<Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    }
+}
+
+fn parse_field(field: &syn::Field) -> Result<Field> {
+    let ident = field.ident.clone().unwrap();
+    let ty = &field.ty;
+    let kind = if matches!(ty, Type::Path(x) if x.path.is_ident("bool")) {
+        Kind::Flag
+    } else if let Some(inner) = inner_type(ty, "Option") {
+        Kind::Optional(inner)
+    } else if let Some(inner) = inner_type(ty, "Vec") {
+        Kind::Multiple(inner)
+    } else {
+        Kind::Required(ty.clone())
+    };
+    let name = ident.to_string();
+    let name = name.trim_start_matches("r#");
+    let mut parsed = Field {
+        ident,
+        kind,
+        long: name.replace('_', "-"),
+        short: None,
+        value_name: name.to_uppercase(),
+        default: None,
+        path: false,
+        hidden: false,
+        help: doc(&field.attrs),
+    };
+    // 属性全部解析后再取首字母，与属性顺序无关
+    let mut short_from_name = false;
+    for attr in field.attrs.iter().filter(|x| x.path().is_ident("arg")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("long") {
+                parsed.long = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("short") {
+                match meta.value() {
+                    Ok(value) => {
+                        parsed.short = Some(value.parse::<syn::LitChar>()?.value());
+                        short_from_name = false;
+                    }
+                    Err(_) => short_from_name = true,
+                }
+            } else if meta.path.is_ident("value_name") {
+                parsed.value_name = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("default") {
+                parsed.default = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("path") {
+                parsed.path = true;
+            } else if meta.path.is_ident("hidden") {
+                parsed.hidden = true;
+            } else {
+                return Err(meta.error(
+                    "expected `long`, `short`, `value_name`, `default`, `path` or `hidden`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    if short_from_name {
+        parsed.short = name.chars().next();
+    }
+    if parsed.default.is_some() && matches!(parsed.kind, Kind::Flag) {
+        return Err(Error::new_spanned(
+            &field.ty,
+            "`default` is not supported for bool flags",
+        ));
+    }
+    Ok(parsed)
+}
+
+/// 与`Opt`的错误提示一致，如`--config/-c`
+fn display(field: &Field) -> String {
+    match field.short {
+        Some(c) => format!("--{}/-{}", field.long, c),
+        None => format!("--{}", field.long),
+    }
+}
+
+fn opt_tokens(field: &Field) -> TokenStream2 {
+    let long = &field.long;
+    let short = field.short.map(|c| c.to_string()).unwrap_or_default();
+    let value_name = &field.value_name;
+    let help = &field.help;
+    let mut opt = match field.kind {
+        Kind::Flag => quote!(::custom_utils::args::Opt::flag(#long, #short)),
+        _ => quote!(::custom_utils::args::Opt::value(#long, #short, #value_name)),
+    };
+    if !help.is_empty() {
+        opt = quote!(#opt.help(#help));
+    }
+    if let Some(default) = &field.default {
+        opt = quote!(#opt.default(#default));
+    }
+    if field.path {
+        opt = quote!(#opt.path());
+    }
+    if field.hidden {
+        opt = quote!(#opt.hidden());
+    }
+    opt
+}
+
+fn read_tokens(field: &Field) -> TokenStream2 {
+    let ident = &field.ident;
+    let long = &field.long;
+    match &field.kind {
+        Kind::Flag => quote!(let #ident = matches.flag(#long);),
+        Kind::Optional(ty) => quote! {
+            let #ident = matches.value_as::<#ty>(#long).unwrap_or_else(|e| {
+                errors.push(e);
+                None
+            });
+        },
+        // 必需选项在此检查而不用`Opt::required`，缺少的选项与类型转换错误一并返回
+        Kind::Required(ty) => {
+            let flag = display(field);
+            quote! {
+                let #ident = match matches.value_as::<#ty>(#long) {
+                    Ok(Some(value)) => Some(value),
+                    Ok(None) => {
+                        errors.push(::custom_utils::args::ArgError::Missing {
+                            flag: #flag.to_string(),
+                        });
+                        None
+                    }
+                    Err(e) => {
+                        errors.push(e);
+                        None
+                    }
+                };
+            }
+        }
+        Kind::Multiple(ty) => quote! {
+            let #ident = matches.values_as::<#ty>(#long).unwrap_or_else(|e| {
+                errors.push(e);
+                ::std::vec::Vec::new()
+            });
+        },
+    }
+}
@@ -1,12 +1,26 @@
-use custom_utils::args::{arg_value, command, exist_arg};
+use custom_utils::args::{arg_value, arg_value_or, command, exist_arg, ArgReader};
 
 /// dns -c ./config/config.yaml -l
 /// dns --config ./config/config.yaml --loop
+/// dns --config=./config/config.yaml -lv
+/// dns -c./config/config.yaml -lv -- --not-an-option
+/// dns -c ./config/config.yaml -l -p 53 -I a -I b
 fn main() {
     assert_eq!(
         arg_value("--config", "-c"),
         Some("./config/config.yaml".to_string())
     );
     assert!(exist_arg("--loop", "-l"));
-    assert_eq!(command(), Some("dns".to_string()))
+    assert!(!exist_arg("--not-an-option", "-x"));
+    assert_eq!(command(), Some("dns".to_string()));
+
+    let port: u16 = arg_value_or("--port", "-p", 53).unwrap();
+    let mut reader = ArgReader::new();
+    let config: String = reader.required("--config", "-c");
+    let includes: Vec<String> = reader.values("--include", "-I");
+    if let Err(e) = reader.finish() {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+    println!("config {} port {} includes {:?}", config, port, includes);
 }
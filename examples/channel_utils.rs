@@ -1,3 +1,4 @@
+#![allow(clippy::unit_arg)]
 use anyhow::{bail, Result};
 use custom_utils::{rx, rx_async, tx, tx_async};
 use log::error;
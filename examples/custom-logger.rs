@@ -1,11 +1,11 @@
 use flexi_logger::writers::LogWriter;
-use flexi_logger::{DeferredNow, FormatFunction, Logger};
+use flexi_logger::{DeferredNow, Logger};
 use log::{debug, info, Record};
 
 pub struct CustomWriter;
 
 impl LogWriter for CustomWriter {
-    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
         println!("[{}]", record.args());
         Ok(())
     }
@@ -20,7 +20,7 @@ impl LogWriter for CustomWriter {
 }
 
 fn main() {
-    let mut logger = Logger::try_with_str("info").unwrap();
+    let logger = Logger::try_with_str("info").unwrap();
     logger
         .log_to_writer(Box::new(CustomWriter))
         .start()
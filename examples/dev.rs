@@ -15,7 +15,7 @@ async fn main() {
     info!("abc");
     warn!("warn");
     error!("error");
-    custom_utils::logger::custom_build(Debug)
+    let _ = custom_utils::logger::custom_build(Debug)
         .module("custom_utils", Debug)
         .build_default()
         .log_to_stdout()
@@ -3,7 +3,7 @@ use custom_utils::tls_util::{gen_rsa_key_pem_and_file, gen_valid_date};
 use picky::x509::certificate::CertificateBuilder;
 use picky::x509::csr::Attribute;
 use picky::x509::extension::KeyUsage;
-use picky::x509::name::{DirectoryName, GeneralName, NameAttr};
+use picky::x509::name::{DirectoryName, NameAttr};
 use picky::x509::{csr::Csr, Extension, Extensions, KeyIdGenMethod};
 use picky::{hash::HashAlgorithm, oids, signature::SignatureAlgorithm};
 
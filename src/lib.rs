@@ -1,15 +1,24 @@
+#[cfg(feature = "actor")]
+mod util_actor;
 mod util_args;
 #[cfg(any(feature = "daemon-sync", feature = "daemon-async"))]
 mod util_daemon;
 #[cfg(feature = "logger")]
 mod util_logger;
+#[cfg(feature = "settings")]
+mod util_settings;
+#[cfg(feature = "shutdown")]
+mod util_shutdown;
 #[cfg(feature = "tls")]
 mod util_tls;
 #[cfg(feature = "tls-util")]
 mod util_tls_util;
 mod util_txrx;
 
-pub use util_txrx::*;
+#[cfg(feature = "actor")]
+pub mod actor {
+    pub use crate::util_actor::*;
+}
 
 pub mod args {
     pub use crate::util_args::*;
@@ -29,6 +38,16 @@ pub mod daemon {
     pub use crate::util_daemon::daemon;
 }
 
+#[cfg(feature = "settings")]
+pub mod settings {
+    pub use crate::util_settings::*;
+}
+
+#[cfg(feature = "shutdown")]
+pub mod shutdown {
+    pub use crate::util_shutdown::*;
+}
+
 #[cfg(feature = "tls")]
 pub mod tls {
     pub use crate::util_tls::*;
@@ -40,6 +59,10 @@ pub mod tls_util {
     pub use crate::util_tls_util::*;
 }
 
+pub mod txrx {
+    pub use crate::util_txrx::*;
+}
+
 #[cfg(feature = "timer")]
 pub mod timer {
     pub use timer_util::*;
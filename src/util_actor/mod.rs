@@ -0,0 +1,187 @@
+use crate::txrx::ChannelError;
+use log::{debug, error, warn};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+/// 持有状态、按顺序处理消息的任务
+pub trait Actor: Send + 'static {
+    type Msg: Send + 'static;
+    type Reply: Send + 'static;
+
+    fn handle(&mut self, msg: Self::Msg) -> impl Future<Output = Self::Reply> + Send;
+
+    /// 开始处理消息前调用（每次重启都会调用）
+    fn started(&mut self) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+    /// 邮箱关闭（所有`Addr`被丢弃）后调用
+    fn stopping(&mut self) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+}
+
+struct Envelope<A: Actor> {
+    msg: A::Msg,
+    reply: Option<oneshot::Sender<A::Reply>>,
+}
+
+type Mailbox<A> = Arc<Mutex<mpsc::Receiver<Envelope<A>>>>;
+
+/// actor的地址，可克隆；全部丢弃后actor退出
+pub struct Addr<A: Actor> {
+    name: Arc<str>,
+    tx: mpsc::Sender<Envelope<A>>,
+}
+
+impl<A: Actor> Clone for Addr<A> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<A: Actor> Addr<A> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+    /// 发送消息，不等待处理结果；邮箱满时等待，可用于`tx_async!`
+    pub async fn send(&self, msg: A::Msg) -> Result<(), ChannelError> {
+        let envelope = Envelope { msg, reply: None };
+        self.tx.send(envelope).await.map_err(|_| {
+            error!("actor [{}] {}", self.name, ChannelError::Closed);
+            ChannelError::Closed
+        })
+    }
+    /// 发送消息并等待`handle`的返回值；处理过程中panic时返回[`ChannelError::ReplyDropped`]
+    pub async fn call(&self, msg: A::Msg) -> Result<A::Reply, ChannelError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let envelope = Envelope {
+            msg,
+            reply: Some(reply_tx),
+        };
+        let rs = match self.tx.send(envelope).await {
+            Ok(_) => reply_rx.await.map_err(|_| ChannelError::ReplyDropped),
+            Err(_) => Err(ChannelError::Closed),
+        };
+        rs.inspect_err(|e| error!("actor [{}] {}", self.name, e))
+    }
+    pub async fn call_timeout(
+        &self,
+        msg: A::Msg,
+        timeout: Duration,
+    ) -> Result<A::Reply, ChannelError> {
+        match tokio::time::timeout(timeout, self.call(msg)).await {
+            Ok(rs) => rs,
+            Err(_) => {
+                error!("actor [{}] {}", self.name, ChannelError::Timeout(timeout));
+                Err(ChannelError::Timeout(timeout))
+            }
+        }
+    }
+}
+
+/// 启动actor，不做监督：panic后actor即终止
+pub fn spawn_actor<A: Actor>(name: &str, actor: A, capacity: usize) -> Addr<A> {
+    let (tx, rx) = mpsc::channel(capacity);
+    let name: Arc<str> = Arc::from(name);
+    tokio::spawn(run(name.clone(), actor, Arc::new(Mutex::new(rx))));
+    Addr { name, tx }
+}
+
+/// 监督者：actor panic后通过`factory`重新创建，按指数退避重启；邮箱中的消息不会丢失
+pub struct Supervisor {
+    name: String,
+    capacity: usize,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    max_restarts: Option<usize>,
+}
+
+impl Supervisor {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            capacity: 32,
+            min_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            max_restarts: None,
+        }
+    }
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+    pub fn backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max;
+        self
+    }
+    /// 超过次数后不再重启；默认不限
+    pub fn max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = Some(max_restarts);
+        self
+    }
+    pub fn spawn<A, F>(self, factory: F) -> Addr<A>
+    where
+        A: Actor,
+        F: Fn() -> A + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(self.capacity);
+        let name: Arc<str> = Arc::from(self.name.as_str());
+        let mailbox = Arc::new(Mutex::new(rx));
+        let addr = Addr {
+            name: name.clone(),
+            tx,
+        };
+        tokio::spawn(async move {
+            let mut backoff = self.min_backoff;
+            let mut restarts = 0;
+            loop {
+                let start = Instant::now();
+                let handle = tokio::spawn(run(name.clone(), factory(), mailbox.clone()));
+                match handle.await {
+                    Err(e) if e.is_panic() => {}
+                    _ => break,
+                }
+                if self.max_restarts.is_some_and(|x| restarts >= x) {
+                    error!("actor [{}] panicked, restarts exhausted", name);
+                    break;
+                }
+                // 稳定运行过一段时间，则重置退避
+                if start.elapsed() > self.max_backoff {
+                    backoff = self.min_backoff;
+                }
+                restarts += 1;
+                warn!(
+                    "actor [{}] panicked, restart({}) in {:?}",
+                    name, restarts, backoff
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(self.max_backoff);
+            }
+        });
+        addr
+    }
+}
+
+async fn run<A: Actor>(name: Arc<str>, mut actor: A, mailbox: Mailbox<A>) {
+    actor.started().await;
+    debug!("actor [{}] started", name);
+    // tokio的Mutex不会因panic中毒，重启后可继续使用同一邮箱
+    let mut rx = mailbox.lock().await;
+    while let Some(envelope) = rx.recv().await {
+        let reply = actor.handle(envelope.msg).await;
+        if let Some(reply_tx) = envelope.reply {
+            let _ = reply_tx.send(reply);
+        }
+    }
+    debug!("actor [{}] stopping", name);
+    actor.stopping().await;
+}
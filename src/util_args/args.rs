@@ -0,0 +1,169 @@
+use crate::util_args::parse::{find_command, find_flag, find_values, Found, OptName};
+use crate::util_args::ArgError;
+use std::ffi::{OsStr, OsString};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// 一组命令行参数（不含程序名），可由任意`OsString`迭代器构造，便于测试：
+/// ```
+/// use custom_utils::args::Args;
+/// let args = Args::new(["dns", "-c", "./config.yaml", "-lv"]);
+/// assert_eq!(args.command(), Some("dns".to_string()));
+/// assert_eq!(args.arg_value("--config", "-c"), Some("./config.yaml".to_string()));
+/// assert!(args.exist_arg("--loop", "-l"));
+/// ```
+/// 非UTF-8参数不会panic：匹配选项及返回`String`时按`to_string_lossy`转换，
+/// `arg_value_os`对单独成项的值返回原始内容
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Args {
+    os: Vec<OsString>,
+    args: Vec<String>,
+    /// 需要值的短选项，`None`表示未声明
+    value_shorts: Option<Vec<char>>,
+}
+
+impl<T: Into<OsString>> FromIterator<T> for Args {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let os: Vec<OsString> = iter.into_iter().map(Into::into).collect();
+        let args = os
+            .iter()
+            .map(|x| x.to_string_lossy().into_owned())
+            .collect();
+        Self {
+            os,
+            args,
+            value_shorts: None,
+        }
+    }
+}
+
+impl Args {
+    pub fn new<T: Into<OsString>>(args: impl IntoIterator<Item = T>) -> Self {
+        args.into_iter().collect()
+    }
+    /// 当前进程的参数，跳过程序名
+    pub fn from_env() -> Self {
+        std::env::args_os().skip(1).collect()
+    }
+    /// 声明需要值的短选项，如`"cI"`，之后`-lvcpath`可拆分为`-l -v -c path`；
+    /// 没有需要值的短选项时传入`""`，以便拆分`-lv`
+    pub fn value_shorts(mut self, shorts: &str) -> Self {
+        self.value_shorts = Some(shorts.chars().filter(|x| *x != '-').collect());
+        self
+    }
+    pub fn as_slice(&self) -> &[String] {
+        &self.args
+    }
+    pub fn as_os_slice(&self) -> &[OsString] {
+        &self.os
+    }
+    /// 从下标`start`开始的参数
+    pub(crate) fn tail(&self, start: usize) -> Args {
+        let args: Args = self.os[start.min(self.os.len())..]
+            .iter()
+            .cloned()
+            .collect();
+        Args {
+            value_shorts: self.value_shorts.clone(),
+            ..args
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// 第一次出现的值；选项名不合法或缺少值时返回`None`
+    pub fn arg_value(&self, long: &str, short: &str) -> Option<String> {
+        self.arg_value_os(long, short)
+            .map(|x| x.to_string_lossy().into_owned())
+    }
+    pub fn arg_value_os(&self, long: &str, short: &str) -> Option<OsString> {
+        let name = OptName::new(long, short).ok()?;
+        let found = find_values(&self.args, &name, self.value_shorts.as_deref())
+            .into_iter()
+            .next()??;
+        Some(self.resolve(found))
+    }
+    pub fn exist_arg(&self, long: &str, short: &str) -> bool {
+        OptName::new(long, short)
+            .is_ok_and(|name| find_flag(&self.args, &name, self.value_shorts.as_deref()))
+    }
+    /// 第一个参数（如子命令）；若第一个参数是选项则返回`None`，`--`之后的第一个参数除外
+    pub fn command(&self) -> Option<String> {
+        find_command(&self.args)
+    }
+
+    /// 重复出现的选项的所有值，如`-I a -I b`
+    pub fn arg_values(&self, long: &str, short: &str) -> Result<Vec<String>, ArgError> {
+        self.arg_values_as(long, short)
+    }
+    /// 解析为`T`，未出现时返回`Ok(None)`
+    pub fn arg_value_as<T>(&self, long: &str, short: &str) -> Result<Option<T>, ArgError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Ok(self.arg_values_as(long, short)?.into_iter().next())
+    }
+    /// 必需的选项，未出现时返回`ArgError::Missing`
+    pub fn arg_value_required<T>(&self, long: &str, short: &str) -> Result<T, ArgError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.arg_value_as(long, short)?
+            .ok_or_else(|| ArgError::Missing {
+                flag: OptName::new(long, short)
+                    .map(|x| x.display())
+                    .unwrap_or_default(),
+            })
+    }
+    /// 未出现时返回`default`
+    pub fn arg_value_or<T>(&self, long: &str, short: &str, default: T) -> Result<T, ArgError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Ok(self.arg_value_as(long, short)?.unwrap_or(default))
+    }
+    /// 重复出现的选项的所有值，均解析为`T`
+    pub fn arg_values_as<T>(&self, long: &str, short: &str) -> Result<Vec<T>, ArgError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let name = OptName::new(long, short)?;
+        find_values(&self.args, &name, self.value_shorts.as_deref())
+            .into_iter()
+            .map(|found| {
+                let value =
+                    found
+                        .map(|x| self.resolve_str(x))
+                        .ok_or_else(|| ArgError::MissingValue {
+                            flag: name.display(),
+                        })?;
+                value.parse().map_err(|e: T::Err| ArgError::Parse {
+                    flag: name.display(),
+                    value,
+                    reason: e.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    fn resolve(&self, found: Found) -> OsString {
+        match found {
+            Found::Next(index) => self.os[index].clone(),
+            Found::Attached(value) => OsStr::new(&value).to_os_string(),
+        }
+    }
+    fn resolve_str(&self, found: Found) -> String {
+        match found {
+            Found::Next(index) => self.args[index].clone(),
+            Found::Attached(value) => value,
+        }
+    }
+}
@@ -0,0 +1,700 @@
+use crate::util_args::parse::{suggest, OptName};
+use crate::util_args::{ArgError, ArgErrors, Args, Shell};
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// 选项声明：开关（`Opt::flag`）或带值选项（`Opt::value`）
+#[derive(Debug, Clone)]
+pub struct Opt {
+    pub(crate) long: String,
+    pub(crate) short: Option<char>,
+    value_name: Option<String>,
+    pub(crate) help: String,
+    default: Option<String>,
+    pub(crate) hidden: bool,
+    pub(crate) path: bool,
+    required: bool,
+    conflicts: Vec<String>,
+    requires: Vec<String>,
+    range: Option<RangeInclusive<i64>>,
+    invalid: Option<ArgError>,
+}
+
+impl Opt {
+    fn new(long: &str, short: &str, value_name: Option<&str>) -> Self {
+        let (name, invalid) = match OptName::new(long, short) {
+            Ok(name) => (name, None),
+            Err(e) => (
+                OptName {
+                    long: long.trim_start_matches('-').to_string(),
+                    short: None,
+                },
+                Some(e),
+            ),
+        };
+        Self {
+            long: name.long,
+            short: name.short,
+            value_name: value_name.map(|x| x.to_string()),
+            help: String::new(),
+            default: None,
+            hidden: false,
+            path: false,
+            required: false,
+            conflicts: Vec::new(),
+            requires: Vec::new(),
+            range: None,
+            invalid,
+        }
+    }
+    /// `short`为空表示没有短选项
+    pub fn flag(long: &str, short: &str) -> Self {
+        Self::new(long, short, None)
+    }
+    /// `value_name`用于帮助信息，如`PATH`
+    pub fn value(long: &str, short: &str, value_name: &str) -> Self {
+        Self::new(long, short, Some(value_name))
+    }
+    pub fn help(mut self, help: &str) -> Self {
+        self.help = help.to_string();
+        self
+    }
+    pub fn default(mut self, default: &str) -> Self {
+        self.default = Some(default.to_string());
+        self
+    }
+    /// 不出现在帮助信息中
+    pub fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    /// 值为文件路径，补全脚本中补全文件名
+    pub fn path(mut self) -> Self {
+        self.path = true;
+        self
+    }
+
+    /// 必须出现；有默认值时视为已满足
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+    /// 不能与`other`同时出现；`other`与`requires`、`Cli::one_of`中的名称须为已声明的长选项
+    pub fn conflicts_with(mut self, other: &str) -> Self {
+        self.conflicts
+            .push(other.trim_start_matches('-').to_string());
+        self
+    }
+    /// 出现时`other`也必须出现
+    pub fn requires(mut self, other: &str) -> Self {
+        self.requires
+            .push(other.trim_start_matches('-').to_string());
+        self
+    }
+    /// 值须为该范围内的整数
+    pub fn range(mut self, range: RangeInclusive<i64>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub fn long(&self) -> &str {
+        &self.long
+    }
+    pub fn short(&self) -> Option<char> {
+        self.short
+    }
+    pub fn takes_value(&self) -> bool {
+        self.value_name.is_some()
+    }
+    /// 用于错误提示，如`--config/-c`
+    fn display(&self) -> String {
+        match self.short {
+            Some(c) => format!("--{}/-{}", self.long, c),
+            None => format!("--{}", self.long),
+        }
+    }
+    /// 帮助信息的左栏，如`-c, --config <PATH>`
+    fn usage(&self) -> String {
+        let short = match self.short {
+            Some(c) => format!("-{}, ", c),
+            None => "    ".to_string(),
+        };
+        match &self.value_name {
+            Some(value) => format!("{}--{} <{}>", short, self.long, value),
+            None => format!("{}--{}", short, self.long),
+        }
+    }
+    fn matches(&self, name: &str) -> bool {
+        let long = name.strip_prefix("--").unwrap_or(name);
+        if long == self.long {
+            return true;
+        }
+        let mut chars = name.strip_prefix('-').unwrap_or(name).chars();
+        matches!((chars.next(), chars.next()), (Some(c), None) if Some(c) == self.short)
+    }
+}
+
+/// 解析结果：正常的参数，或请求了`--help`/`--version`
+#[derive(Debug, Clone)]
+pub enum Parsed {
+    Matches(Matches),
+    Help(String),
+    Version(String),
+    /// 隐藏选项`--generate-completions <shell>`生成的补全脚本
+    Completions(String),
+}
+
+const COMPLETIONS: &str = "generate-completions";
+
+type HandlerFn = dyn Fn(&Matches) -> anyhow::Result<()> + Send + Sync;
+
+/// 子命令的处理函数，参数为该子命令范围内的解析结果
+#[derive(Clone)]
+pub struct Handler(Arc<HandlerFn>);
+
+impl Debug for Handler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handler")
+    }
+}
+
+/// 已声明选项的命令行工具，自动提供`--help`/`-h`，设置了版本时提供`--version`/`-V`。
+/// 可嵌套子命令，每个子命令有自己的选项与处理函数，见`Cli::run`
+/// ```
+/// use custom_utils::args::{Args, Cli, Opt, Parsed};
+/// let cli = Cli::new("dns")
+///     .version("1.0.0")
+///     .opt(Opt::value("--config", "-c", "PATH").help("config file"))
+///     .opt(Opt::flag("--loop", "-l").help("run forever"));
+/// let matches = match cli.try_parse(Args::new(["-lc", "a.toml"])).unwrap() {
+///     Parsed::Matches(matches) => matches,
+///     _ => unreachable!(),
+/// };
+/// assert_eq!(matches.value("config"), Some("a.toml".to_string()));
+/// assert!(matches.flag("loop"));
+/// assert!(cli.try_parse(Args::new(["--confg", "a.toml"])).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cli {
+    pub(crate) name: String,
+    version: Option<String>,
+    pub(crate) about: Option<String>,
+    opts: Vec<Opt>,
+    pub(crate) subcommands: Vec<Cli>,
+    handler: Option<Handler>,
+    groups: Vec<Vec<String>>,
+}
+
+impl Cli {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: None,
+            about: None,
+            opts: Vec::new(),
+            subcommands: Vec::new(),
+            handler: None,
+            groups: Vec::new(),
+        }
+    }
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+    pub fn about(mut self, about: &str) -> Self {
+        self.about = Some(about.to_string());
+        self
+    }
+    /// 这组选项中必须恰好出现一个
+    pub fn one_of(mut self, group: &[&str]) -> Self {
+        let group = group.iter().map(|x| x.trim_start_matches('-').to_string());
+        self.groups.push(group.collect());
+        self
+    }
+    pub fn opt(mut self, opt: Opt) -> Self {
+        self.opts.push(opt);
+        self
+    }
+
+    /// 子命令，`--`之前的第一个位置参数作为子命令名
+    pub fn subcommand(mut self, cli: Cli) -> Self {
+        self.subcommands.push(cli);
+        self
+    }
+    pub fn handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&Matches) -> anyhow::Result<()> + Send + Sync + 'static,
+    {
+        self.handler = Some(Handler(Arc::new(handler)));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn subcommands(&self) -> &[Cli] {
+        &self.subcommands
+    }
+    /// 含内置的`--help`/`--version`
+    pub fn opts(&self) -> Vec<Opt> {
+        let mut opts = self.opts.clone();
+        let declared = |long: &str, short: char| {
+            let short = self.opts.iter().any(|x| x.short == Some(short));
+            (self.opts.iter().any(|x| x.long == long), short)
+        };
+        match declared("help", 'h') {
+            (false, false) => opts.push(Opt::flag("help", "h").help("Print help")),
+            (false, true) => opts.push(Opt::flag("help", "").help("Print help")),
+            _ => {}
+        }
+        if self.version.is_some() {
+            match declared("version", 'V') {
+                (false, false) => opts.push(Opt::flag("version", "V").help("Print version")),
+                (false, true) => opts.push(Opt::flag("version", "").help("Print version")),
+                _ => {}
+            }
+        }
+        opts
+    }
+
+    pub fn help(&self) -> String {
+        self.help_for(&self.name)
+    }
+    /// `path`为含上级命令的完整命令名，如`dns start`
+    fn help_for(&self, path: &str) -> String {
+        let mut text = String::new();
+        match &self.version {
+            Some(version) => text.push_str(&format!("{} {}\n", path, version)),
+            None => text.push_str(&format!("{}\n", path)),
+        }
+        if let Some(about) = &self.about {
+            text.push_str(&format!("{}\n", about));
+        }
+        if self.subcommands.is_empty() {
+            text.push_str(&format!("\nUsage: {} [OPTIONS]\n", path));
+        } else {
+            text.push_str(&format!(
+                "\nUsage: {} [OPTIONS] <COMMAND>\n\nCommands:\n",
+                path
+            ));
+            let width = self
+                .subcommands
+                .iter()
+                .map(|x| x.name.len())
+                .max()
+                .unwrap_or(0);
+            for cli in &self.subcommands {
+                let line = format!(
+                    "  {:width$}  {}",
+                    cli.name,
+                    cli.about.as_deref().unwrap_or("")
+                );
+                text.push_str(line.trim_end());
+                text.push('\n');
+            }
+        }
+        text.push_str("\nOptions:\n");
+        let opts: Vec<Opt> = self.opts().into_iter().filter(|x| !x.hidden).collect();
+        let width = opts.iter().map(|x| x.usage().len()).max().unwrap_or(0);
+        for opt in opts {
+            let mut line = format!("  {:width$}  {}", opt.usage(), opt.help);
+            if let Some(default) = &opt.default {
+                line.push_str(&format!(" [default: {}]", default));
+            }
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+    pub fn version_text(&self) -> String {
+        format!("{} {}", self.name, self.version.as_deref().unwrap_or(""))
+            .trim_end()
+            .to_string()
+    }
+
+    /// 解析当前进程的参数：`--help`/`--version`输出后退出，参数错误时输出错误并以2退出
+    pub fn parse(&self) -> Matches {
+        match self.try_parse(Args::from_env()) {
+            Ok(Parsed::Matches(matches)) => matches,
+            Ok(Parsed::Help(text)) | Ok(Parsed::Version(text)) | Ok(Parsed::Completions(text)) => {
+                println!("{}", text.trim_end());
+                std::process::exit(0)
+            }
+            Err(e) => {
+                eprintln!("{}\n\nFor more information, try '--help'.", e);
+                std::process::exit(2)
+            }
+        }
+    }
+
+    /// 解析当前进程的参数并调用对应子命令的处理函数
+    /// ```no_run
+    /// use custom_utils::args::{Cli, Opt};
+    /// Cli::new("dns")
+    ///     .subcommand(
+    ///         Cli::new("start")
+    ///             .about("start the server")
+    ///             .opt(Opt::value("--port", "-p", "PORT").default("53"))
+    ///             .handler(|m| {
+    ///                 let port: u16 = m.value_as("port")?.unwrap();
+    ///                 println!("listen on {}", port);
+    ///                 Ok(())
+    ///             }),
+    ///     )
+    ///     .subcommand(Cli::new("stop").handler(|_| Ok(())))
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn run(&self) -> anyhow::Result<()> {
+        self.dispatch(&self.parse())
+    }
+
+    /// 调用`matches`中最内层子命令的处理函数；该命令没有处理函数但有子命令时返回`MissingCommand`
+    pub fn dispatch(&self, matches: &Matches) -> anyhow::Result<()> {
+        if let Some((name, sub)) = matches.subcommand() {
+            if let Some(cli) = self.subcommands.iter().find(|x| x.name == name) {
+                return cli.dispatch(sub);
+            }
+        }
+        match &self.handler {
+            Some(handler) => (handler.0)(matches),
+            None if self.subcommands.is_empty() => Ok(()),
+            None => Err(ArgErrors::from(ArgError::MissingCommand {
+                available: self.command_names(),
+            })
+            .into()),
+        }
+    }
+
+    /// 解析`args`，所有错误一并返回
+    pub fn try_parse(&self, args: Args) -> Result<Parsed, ArgErrors> {
+        self.parse_scope(args, &self.name, true)
+    }
+
+    /// 检查所有约束，违反的约束全部记入`errors`
+    /// 约束中引用了未声明的选项，通常是拼写错误，否则约束会静默失效
+    fn undeclared(&self, opts: &[Opt]) -> Vec<ArgError> {
+        let declared = |name: &String| opts.iter().any(|x| x.long == *name);
+        let mut errors = Vec::new();
+        for opt in opts {
+            let names = opt.conflicts.iter().chain(&opt.requires);
+            for name in names.filter(|x| !declared(x)) {
+                errors.push(ArgError::InvalidName(format!(
+                    "--{} (in constraints of {})",
+                    name,
+                    opt.display()
+                )));
+            }
+        }
+        for name in self.groups.iter().flatten().filter(|x| !declared(x)) {
+            errors.push(ArgError::InvalidName(format!("--{} (in one_of)", name)));
+        }
+        errors
+    }
+
+    fn check_constraints(
+        &self,
+        opts: &[Opt],
+        found: &[(usize, Option<String>)],
+        errors: &mut Vec<ArgError>,
+    ) {
+        let present = |long: &str| found.iter().any(|(i, _)| opts[*i].long == long);
+        let display = |long: &str| match opts.iter().find(|x| x.long == long) {
+            Some(opt) => opt.display(),
+            None => format!("--{}", long),
+        };
+        for opt in opts {
+            if present(&opt.long) {
+                for other in opt.conflicts.iter().filter(|x| present(x)) {
+                    errors.push(ArgError::Conflict {
+                        flag: opt.display(),
+                        other: display(other),
+                    });
+                }
+                for other in opt.requires.iter().filter(|x| !present(x)) {
+                    errors.push(ArgError::Requires {
+                        flag: opt.display(),
+                        required: display(other),
+                    });
+                }
+            } else if opt.required && opt.default.is_none() {
+                errors.push(ArgError::Missing {
+                    flag: opt.display(),
+                });
+            }
+            let Some(range) = &opt.range else {
+                continue;
+            };
+            let values = found.iter().filter(|(i, _)| opts[*i].long == opt.long);
+            for value in values.filter_map(|x| x.1.as_ref()) {
+                let number = match value.parse::<i64>() {
+                    Ok(number) => number,
+                    Err(e) => {
+                        errors.push(ArgError::Parse {
+                            flag: opt.display(),
+                            value: value.clone(),
+                            reason: e.to_string(),
+                        });
+                        continue;
+                    }
+                };
+                if !range.contains(&number) {
+                    errors.push(ArgError::OutOfRange {
+                        flag: opt.display(),
+                        value: value.clone(),
+                        min: *range.start(),
+                        max: *range.end(),
+                    });
+                }
+            }
+        }
+        for group in &self.groups {
+            let count = group.iter().filter(|x| present(x)).count();
+            if count != 1 {
+                errors.push(ArgError::OneOf {
+                    flags: group.iter().map(|x| display(x)).collect(),
+                    found: count,
+                });
+            }
+        }
+    }
+
+    fn command_names(&self) -> Vec<String> {
+        self.subcommands.iter().map(|x| x.name.clone()).collect()
+    }
+
+    fn parse_scope(&self, args: Args, path: &str, root: bool) -> Result<Parsed, ArgErrors> {
+        let mut opts = self.opts();
+        if root {
+            opts.push(Opt::value(COMPLETIONS, "", "SHELL").hidden());
+        }
+        let mut errors: Vec<ArgError> = opts.iter().filter_map(|x| x.invalid.clone()).collect();
+        errors.extend(self.undeclared(&opts));
+        let mut found = Vec::new();
+        let mut positionals = Vec::new();
+        let mut subcommand = None;
+        let tokens = args.as_slice();
+        let mut index = 0;
+        while index < tokens.len() {
+            let arg = &tokens[index];
+            index += 1;
+            if arg == "--" {
+                positionals.extend(tokens[index..].iter().cloned());
+                break;
+            } else if let Some(body) = arg.strip_prefix("--") {
+                let (name, attached) = match body.split_once('=') {
+                    Some((name, val)) => (name, Some(val.to_string())),
+                    None => (body, None),
+                };
+                let Some(opt_index) = opts.iter().position(|x| x.long == name) else {
+                    let visible = opts.iter().filter(|x| !x.hidden).map(|x| x.long.as_str());
+                    errors.push(ArgError::Unknown {
+                        flag: format!("--{}", name),
+                        suggestion: suggest(name, visible).map(|x| format!("--{}", x)),
+                    });
+                    continue;
+                };
+                let opt = &opts[opt_index];
+                if opt.takes_value() {
+                    let value = attached.or_else(|| {
+                        index += 1;
+                        tokens.get(index - 1).cloned()
+                    });
+                    match value {
+                        Some(value) => found.push((opt_index, Some(value))),
+                        None => errors.push(ArgError::MissingValue {
+                            flag: opt.display(),
+                        }),
+                    }
+                } else if attached.is_some() {
+                    errors.push(ArgError::UnexpectedValue {
+                        flag: opt.display(),
+                    });
+                } else {
+                    found.push((opt_index, None));
+                }
+            } else if let Some(cluster) = arg.strip_prefix('-').filter(|x| !x.is_empty()) {
+                for (pos, c) in cluster.char_indices() {
+                    let Some(opt_index) = opts.iter().position(|x| x.short == Some(c)) else {
+                        errors.push(ArgError::Unknown {
+                            flag: format!("-{}", c),
+                            suggestion: None,
+                        });
+                        continue;
+                    };
+                    let opt = &opts[opt_index];
+                    if !opt.takes_value() {
+                        found.push((opt_index, None));
+                        continue;
+                    }
+                    let rest = &cluster[pos + c.len_utf8()..];
+                    let value = if rest.is_empty() {
+                        index += 1;
+                        tokens.get(index - 1).cloned()
+                    } else {
+                        Some(rest.to_string())
+                    };
+                    match value {
+                        Some(value) => found.push((opt_index, Some(value))),
+                        None => errors.push(ArgError::MissingValue {
+                            flag: opt.display(),
+                        }),
+                    }
+                    break;
+                }
+            } else if self.subcommands.is_empty() {
+                positionals.push(arg.clone());
+            } else {
+                match self.subcommands.iter().find(|x| x.name == *arg) {
+                    Some(cli) => {
+                        let path = format!("{} {}", path, cli.name);
+                        subcommand = Some((cli, cli.parse_scope(args.tail(index), &path, false)));
+                    }
+                    None => errors.push(ArgError::UnknownCommand {
+                        command: arg.clone(),
+                        suggestion: suggest(arg, self.subcommands.iter().map(|x| x.name.as_str()))
+                            .map(|x| x.to_string()),
+                        available: self.command_names(),
+                    }),
+                }
+                break;
+            }
+        }
+        let requested = |long: &str| found.iter().any(|(i, _)| opts[*i].long == long);
+        if requested("help") && !self.opts.iter().any(|x| x.long == "help") {
+            return Ok(Parsed::Help(self.help_for(path)));
+        }
+        if requested("version") && !self.opts.iter().any(|x| x.long == "version") {
+            return Ok(Parsed::Version(self.version_text()));
+        }
+        let shell = found
+            .iter()
+            .find(|(i, _)| opts[*i].long == COMPLETIONS)
+            .and_then(|x| x.1.as_ref());
+        if let Some(shell) = shell {
+            return match shell.parse::<Shell>() {
+                Ok(shell) => Ok(Parsed::Completions(self.completions(shell))),
+                Err(reason) => Err(ArgErrors::from(ArgError::Parse {
+                    flag: format!("--{}", COMPLETIONS),
+                    value: shell.clone(),
+                    reason,
+                })),
+            };
+        }
+        self.check_constraints(&opts, &found, &mut errors);
+        let subcommand = match subcommand {
+            Some((cli, Ok(Parsed::Matches(matches)))) => {
+                Some((cli.name.clone(), Box::new(matches)))
+            }
+            Some((_, Ok(parsed))) => return Ok(parsed),
+            Some((_, Err(e))) => {
+                errors.extend(e.0);
+                None
+            }
+            None => None,
+        };
+        if !errors.is_empty() {
+            return Err(ArgErrors(errors));
+        }
+        let value_shorts: String = opts
+            .iter()
+            .filter(|x| x.takes_value())
+            .filter_map(|x| x.short)
+            .collect();
+        Ok(Parsed::Matches(Matches {
+            opts,
+            found,
+            positionals,
+            subcommand,
+            args: args.value_shorts(&value_shorts),
+        }))
+    }
+}
+
+/// 按声明解析后的参数
+#[derive(Debug, Clone)]
+pub struct Matches {
+    opts: Vec<Opt>,
+    /// (选项下标, 值)
+    found: Vec<(usize, Option<String>)>,
+    positionals: Vec<String>,
+    subcommand: Option<(String, Box<Matches>)>,
+    args: Args,
+}
+
+impl Matches {
+    /// 选项名写作`--config`、`config`或`-c`均可
+    pub fn flag(&self, name: &str) -> bool {
+        self.occurrences(name) > 0
+    }
+    pub fn occurrences(&self, name: &str) -> usize {
+        self.found_values(name).count()
+    }
+    /// 第一次出现的值，未出现时为默认值
+    pub fn value(&self, name: &str) -> Option<String> {
+        self.values(name).into_iter().next()
+    }
+    /// 所有出现的值，未出现时为默认值
+    pub fn values(&self, name: &str) -> Vec<String> {
+        let values: Vec<String> = self.found_values(name).flatten().cloned().collect();
+        if !values.is_empty() {
+            return values;
+        }
+        self.find_opt(name)
+            .and_then(|x| x.default.clone())
+            .into_iter()
+            .collect()
+    }
+    pub fn value_as<T>(&self, name: &str) -> Result<Option<T>, ArgError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Ok(self.values_as(name)?.into_iter().next())
+    }
+    pub fn values_as<T>(&self, name: &str) -> Result<Vec<T>, ArgError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let flag = self.find_opt(name).map(|x| x.display()).unwrap_or_default();
+        self.values(name)
+            .into_iter()
+            .map(|value| {
+                value.parse().map_err(|e: T::Err| ArgError::Parse {
+                    flag: flag.clone(),
+                    value,
+                    reason: e.to_string(),
+                })
+            })
+            .collect()
+    }
+    /// 非选项参数，含`--`之后的所有参数
+    pub fn positionals(&self) -> &[String] {
+        &self.positionals
+    }
+    /// 子命令名及其范围内的解析结果
+    pub fn subcommand(&self) -> Option<(&str, &Matches)> {
+        self.subcommand
+            .as_ref()
+            .map(|(name, m)| (name.as_str(), m.as_ref()))
+    }
+    /// 本命令范围内的原始参数，子命令的`Matches`只含子命令名之后的参数；
+    /// 已按声明的带值选项设置`Args::value_shorts`
+    pub fn args(&self) -> &Args {
+        &self.args
+    }
+    fn find_opt(&self, name: &str) -> Option<&Opt> {
+        self.opts.iter().find(|x| x.matches(name))
+    }
+    fn found_values(&self, name: &str) -> impl Iterator<Item = &Option<String>> + '_ {
+        let index = self.opts.iter().position(|x| x.matches(name));
+        self.found
+            .iter()
+            .filter(move |(i, _)| Some(*i) == index)
+            .map(|x| &x.1)
+    }
+}
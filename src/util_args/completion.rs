@@ -0,0 +1,275 @@
+use crate::util_args::{Cli, Opt};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err("expected one of bash, zsh, fish".to_string()),
+        }
+    }
+}
+
+impl Display for Shell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Shell::Bash => write!(f, "bash"),
+            Shell::Zsh => write!(f, "zsh"),
+            Shell::Fish => write!(f, "fish"),
+        }
+    }
+}
+
+/// 一层命令：完整路径（如`dns service`）及其声明
+struct Scope<'a> {
+    path: Vec<&'a str>,
+    cli: &'a Cli,
+    opts: Vec<Opt>,
+}
+
+impl Scope<'_> {
+    fn path(&self) -> String {
+        self.path.join(" ")
+    }
+}
+
+fn scopes(cli: &Cli) -> Vec<Scope<'_>> {
+    fn walk<'a>(cli: &'a Cli, mut path: Vec<&'a str>, out: &mut Vec<Scope<'a>>) {
+        path.push(&cli.name);
+        out.push(Scope {
+            path: path.clone(),
+            cli,
+            opts: cli.opts().into_iter().filter(|x| !x.hidden).collect(),
+        });
+        for sub in &cli.subcommands {
+            walk(sub, path.clone(), out);
+        }
+    }
+    let mut out = Vec::new();
+    walk(cli, Vec::new(), &mut out);
+    out
+}
+
+impl Cli {
+    /// 生成补全脚本，包含所有可见选项与子命令，`Opt::path`的值补全文件名。
+    /// 也可通过隐藏选项`--generate-completions <shell>`输出，如：
+    /// `dns --generate-completions bash > /etc/bash_completion.d/dns`
+    pub fn completions(&self, shell: Shell) -> String {
+        let scopes = scopes(self);
+        match shell {
+            Shell::Bash => bash(&self.name, &scopes),
+            Shell::Zsh => zsh(&self.name, &scopes),
+            Shell::Fish => fish(&self.name, &scopes),
+        }
+    }
+}
+
+fn function_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// 按已输入的词确定当前所在的子命令
+fn walk_words(scopes: &[Scope], indent: &str) -> String {
+    let mut text = String::new();
+    for scope in scopes {
+        for sub in &scope.cli.subcommands {
+            text.push_str(&format!(
+                "{}\"{}:{}\") cmd=\"{} {}\" ;;\n",
+                indent,
+                scope.path(),
+                sub.name,
+                scope.path(),
+                sub.name
+            ));
+        }
+    }
+    text
+}
+
+/// `--config|-c`形式的选项名
+fn value_patterns(opt: &Opt) -> String {
+    match opt.short {
+        Some(c) => format!("--{}|-{}", opt.long, c),
+        None => format!("--{}", opt.long),
+    }
+}
+
+fn bash(name: &str, scopes: &[Scope]) -> String {
+    let func = function_name(name);
+    let mut text = format!(
+        "_{func}() {{
+    local cur prev cmd=\"{name}\" i
+    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"
+    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case \"${{cmd}}:${{COMP_WORDS[i]}}\" in
+{}        esac
+    done
+    case \"${{cmd}}\" in
+",
+        walk_words(scopes, "            ")
+    );
+    for scope in scopes {
+        text.push_str(&format!("        \"{}\")\n", scope.path()));
+        text.push_str("            case \"${prev}\" in\n");
+        for opt in scope.opts.iter().filter(|x| x.takes_value()) {
+            let reply = if opt.path {
+                "COMPREPLY=($(compgen -f -- \"${cur}\"))"
+            } else {
+                "COMPREPLY=()"
+            };
+            text.push_str(&format!(
+                "                {}) {}; return ;;\n",
+                value_patterns(opt),
+                reply
+            ));
+        }
+        text.push_str("            esac\n");
+        let mut words: Vec<String> = Vec::new();
+        for opt in &scope.opts {
+            words.push(format!("--{}", opt.long));
+            if let Some(c) = opt.short {
+                words.push(format!("-{}", c));
+            }
+        }
+        words.extend(scope.cli.subcommands.iter().map(|x| x.name.clone()));
+        text.push_str(&format!(
+            "            COMPREPLY=($(compgen -W \"{}\" -- \"${{cur}}\"))\n            ;;\n",
+            words.join(" ")
+        ));
+    }
+    text.push_str(&format!("    esac\n}}\ncomplete -F _{func} {name}\n"));
+    text
+}
+
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''").replace(':', "\\:")
+}
+
+fn zsh(name: &str, scopes: &[Scope]) -> String {
+    let func = function_name(name);
+    let mut text = format!(
+        "#compdef {name}
+
+_{func}() {{
+    local cmd=\"{name}\" prev=\"${{words[CURRENT-1]}}\" i
+    for ((i = 2; i < CURRENT; i++)); do
+        case \"${{cmd}}:${{words[i]}}\" in
+{}        esac
+    done
+    case \"${{cmd}}\" in
+",
+        walk_words(scopes, "            ")
+    );
+    for scope in scopes {
+        text.push_str(&format!("        \"{}\")\n", scope.path()));
+        text.push_str("            case \"${prev}\" in\n");
+        for opt in scope.opts.iter().filter(|x| x.takes_value()) {
+            let action = if opt.path { "_files; return" } else { "return" };
+            text.push_str(&format!(
+                "                {}) {} ;;\n",
+                value_patterns(opt),
+                action
+            ));
+        }
+        text.push_str("            esac\n");
+        let mut opts = Vec::new();
+        for opt in &scope.opts {
+            opts.push(format!("'--{}:{}'", opt.long, zsh_escape(&opt.help)));
+            if let Some(c) = opt.short {
+                opts.push(format!("'-{}:{}'", c, zsh_escape(&opt.help)));
+            }
+        }
+        let cmds: Vec<String> = scope
+            .cli
+            .subcommands
+            .iter()
+            .map(|x| {
+                let about = x.about.as_deref().unwrap_or("");
+                format!("'{}:{}'", zsh_escape(&x.name), zsh_escape(about))
+            })
+            .collect();
+        text.push_str(&format!(
+            "            local -a opts=({}) cmds=({})\n",
+            opts.join(" "),
+            cmds.join(" ")
+        ));
+        text.push_str("            _describe -t commands 'command' cmds\n");
+        text.push_str("            _describe -t options 'option' opts\n            ;;\n");
+    }
+    text.push_str(&format!("    esac\n}}\n\n_{func} \"$@\"\n"));
+    text
+}
+
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish(name: &str, scopes: &[Scope]) -> String {
+    let mut text = format!("complete -c {} -f\n", name);
+    for scope in scopes {
+        let children: Vec<&str> = scope
+            .cli
+            .subcommands
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect();
+        // 当前层：已输入本层命令名，且尚未输入下一层子命令
+        let mut conditions = Vec::new();
+        if scope.path.len() > 1 {
+            conditions.push(format!(
+                "__fish_seen_subcommand_from {}",
+                scope.path[scope.path.len() - 1]
+            ));
+        }
+        if !children.is_empty() {
+            conditions.push(format!(
+                "not __fish_seen_subcommand_from {}",
+                children.join(" ")
+            ));
+        }
+        let condition = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" -n '{}'", conditions.join("; and "))
+        };
+        for sub in &scope.cli.subcommands {
+            text.push_str(&format!(
+                "complete -c {}{} -a {} -d '{}'\n",
+                name,
+                condition,
+                sub.name,
+                fish_escape(sub.about.as_deref().unwrap_or(""))
+            ));
+        }
+        for opt in &scope.opts {
+            let mut line = format!("complete -c {}{} -l {}", name, condition, opt.long);
+            if let Some(c) = opt.short {
+                line.push_str(&format!(" -s {}", c));
+            }
+            if opt.takes_value() {
+                line.push_str(if opt.path { " -r -F" } else { " -x" });
+            }
+            if !opt.help.is_empty() {
+                line.push_str(&format!(" -d '{}'", fish_escape(&opt.help)));
+            }
+            text.push_str(&line);
+            text.push('\n');
+        }
+    }
+    text
+}
@@ -0,0 +1,42 @@
+use crate::util_args::{ArgErrors, Cli, Matches};
+
+/// 由选项声明构造的参数结构体，通常由`#[derive(CustomArgs)]`（feature `derive`）生成：
+/// ```ignore
+/// use custom_utils::args::CustomArgs;
+///
+/// /// a tiny dns server
+/// #[derive(CustomArgs)]
+/// struct Opts {
+///     /// config file
+///     #[arg(short = 'c', value_name = "PATH", path)]
+///     config: Option<std::path::PathBuf>,
+///     /// listen port
+///     #[arg(short, default = "53")]
+///     port: u16,
+///     /// upstream servers, repeatable
+///     #[arg(short = 'u')]
+///     upstream: Vec<String>,
+///     /// run forever
+///     #[arg(short = 'l', long = "loop")]
+///     forever: bool,
+/// }
+///
+/// let opts = Opts::parse();
+/// ```
+/// 字段类型决定选项的形式：`bool`为开关，`Option<T>`为可选，`Vec<T>`可重复出现，
+/// 其他类型为必需（设置`default`时除外）；`T`需实现`FromStr`
+pub trait CustomArgs: Sized {
+    fn cli() -> Cli;
+    fn from_matches(matches: &Matches) -> Result<Self, ArgErrors>;
+
+    /// 解析当前进程的参数，`--help`/`--version`及参数错误的处理同`Cli::parse`
+    fn parse() -> Self {
+        match Self::from_matches(&Self::cli().parse()) {
+            Ok(args) => args,
+            Err(e) => {
+                eprintln!("{}\n\nFor more information, try '--help'.", e);
+                std::process::exit(2)
+            }
+        }
+    }
+}
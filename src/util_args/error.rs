@@ -0,0 +1,131 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgError {
+    /// 选项名不合法，如短选项不止一个字符
+    InvalidName(String),
+    /// 缺少必需的选项
+    Missing { flag: String },
+    /// 选项后缺少值
+    MissingValue { flag: String },
+    /// 未声明的选项，附带最相近的选项
+    Unknown {
+        flag: String,
+        suggestion: Option<String>,
+    },
+    /// 未注册的子命令
+    UnknownCommand {
+        command: String,
+        suggestion: Option<String>,
+        available: Vec<String>,
+    },
+    /// 需要子命令但未提供
+    MissingCommand { available: Vec<String> },
+    /// 开关选项不接受值，如`--loop=1`
+    UnexpectedValue { flag: String },
+    /// 两个选项不能同时出现
+    Conflict { flag: String, other: String },
+    /// `flag`出现时`required`也必须出现
+    Requires { flag: String, required: String },
+    /// 一组选项中必须恰好出现一个
+    OneOf { flags: Vec<String>, found: usize },
+    /// 值不在允许的范围内
+    OutOfRange {
+        flag: String,
+        value: String,
+        min: i64,
+        max: i64,
+    },
+    /// 值无法解析为目标类型
+    Parse {
+        flag: String,
+        value: String,
+        reason: String,
+    },
+}
+
+/// 汇总的参数错误
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArgErrors(pub Vec<ArgError>);
+
+impl Display for ArgError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgError::InvalidName(name) => write!(f, "invalid option name: {}", name),
+            ArgError::Missing { flag } => write!(f, "missing required option {}", flag),
+            ArgError::MissingValue { flag } => write!(f, "option {} requires a value", flag),
+            ArgError::Unknown { flag, suggestion } => {
+                write!(f, "unknown option {}", flag)?;
+                match suggestion {
+                    Some(x) => write!(f, ", did you mean {}?", x),
+                    None => Ok(()),
+                }
+            }
+            ArgError::UnknownCommand {
+                command,
+                suggestion,
+                available,
+            } => {
+                write!(f, "unknown command '{}'", command)?;
+                if let Some(x) = suggestion {
+                    write!(f, ", did you mean '{}'?", x)?;
+                }
+                write!(f, " available commands: {}", available.join(", "))
+            }
+            ArgError::MissingCommand { available } => {
+                write!(f, "missing command, available: {}", available.join(", "))
+            }
+            ArgError::UnexpectedValue { flag } => {
+                write!(f, "option {} does not take a value", flag)
+            }
+            ArgError::Conflict { flag, other } => {
+                write!(f, "option {} cannot be used with {}", flag, other)
+            }
+            ArgError::Requires { flag, required } => {
+                write!(f, "option {} requires {}", flag, required)
+            }
+            ArgError::OneOf { flags, found: 0 } => {
+                write!(f, "one of {} is required", flags.join(", "))
+            }
+            ArgError::OneOf { flags, .. } => {
+                write!(f, "only one of {} can be used", flags.join(", "))
+            }
+            ArgError::OutOfRange {
+                flag,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "value '{}' for {} is out of range {}..={}",
+                value, flag, min, max
+            ),
+            ArgError::Parse {
+                flag,
+                value,
+                reason,
+            } => write!(f, "invalid value '{}' for {}: {}", value, flag, reason),
+        }
+    }
+}
+
+impl Display for ArgErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, err) in self.0.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "error: {}", err)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ArgError {}
+impl std::error::Error for ArgErrors {}
+
+impl From<ArgError> for ArgErrors {
+    fn from(err: ArgError) -> Self {
+        ArgErrors(vec![err])
+    }
+}
@@ -1,37 +1,180 @@
+//! 命令行参数的简单读取，支持GNU风格：
+//!     `--config path`、`--config=path`
+//!     `-c path`、`-cpath`、合并的短选项`-lv`（需声明`Args::value_shorts`）
+//!     `--`之后的参数均视为位置参数
+//!
+//! 合并短选项需要知道哪些短选项带值：未声明时只有第一个字符是选项，
+//! 其后的内容都是它的值，`exist_arg`与`arg_value`都把`-cpath`读作`-c path`。
+//! 用`Args::value_shorts`声明后从左到右读取，需要值的短选项之后的内容都是它的值，
+//! 非字母数字字符之后不再视为选项；`Cli`解析结果的`Matches::args`已按声明填好
+//!
+//! 选项名写作`--config`或`config`、`-c`或`c`均可，`short`为空表示没有短选项。
+//! 需要类型转换或汇总报错时使用`arg_value_as`等函数或`ArgReader`；
+//! 自由函数读取当前进程参数，`Args`可由任意参数列表构造。
+//! 需要`--help`及未知选项检查时，用`Cli`声明所有选项
+
+mod args;
+mod cli;
+mod completion;
+mod custom;
+mod error;
+mod parse;
+
+pub use args::*;
+pub use cli::*;
+pub use completion::*;
+pub use custom::*;
+#[cfg(feature = "derive")]
+pub use custom_utils_derive::CustomArgs;
+pub use error::*;
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// 第一次出现的值；选项名不合法或缺少值时返回`None`
 pub fn arg_value(long: &str, short: &str) -> Option<String> {
-    assert!(long.starts_with("--"));
-    assert!(short.starts_with("-"));
-    let mut is_val = false;
-    for arg in std::env::args() {
-        if is_val {
-            return Some(arg);
-        }
-        if arg == long {
-            is_val = true;
-        } else if arg == short {
-            is_val = true;
-        }
-    }
-    None
+    Args::from_env().arg_value(long, short)
 }
 
 pub fn exist_arg(long: &str, short: &str) -> bool {
-    assert!(long.starts_with("--"));
-    assert!(short.starts_with("-"));
-    for arg in std::env::args() {
-        if arg == long {
-            return true;
-        } else if arg == short {
-            return true;
-        }
-    }
-    false
+    Args::from_env().exist_arg(long, short)
 }
+
+/// 第一个参数（如子命令）；若第一个参数是选项则返回`None`，`--`之后的第一个参数除外
 pub fn command() -> Option<String> {
-    for (index, arg) in std::env::args().enumerate() {
-        if index == 1 {
-            return Some(arg);
+    Args::from_env().command()
+}
+
+/// 重复出现的选项的所有值，如`-I a -I b`
+pub fn arg_values(long: &str, short: &str) -> Result<Vec<String>, ArgError> {
+    Args::from_env().arg_values(long, short)
+}
+
+/// 解析为`T`，未出现时返回`Ok(None)`
+pub fn arg_value_as<T>(long: &str, short: &str) -> Result<Option<T>, ArgError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Args::from_env().arg_value_as(long, short)
+}
+
+/// 必需的选项，未出现时返回`ArgError::Missing`
+pub fn arg_value_required<T>(long: &str, short: &str) -> Result<T, ArgError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Args::from_env().arg_value_required(long, short)
+}
+
+/// 未出现时返回`default`
+pub fn arg_value_or<T>(long: &str, short: &str, default: T) -> Result<T, ArgError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Args::from_env().arg_value_or(long, short, default)
+}
+
+/// 重复出现的选项的所有值，均解析为`T`
+pub fn arg_values_as<T>(long: &str, short: &str) -> Result<Vec<T>, ArgError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Args::from_env().arg_values_as(long, short)
+}
+
+/// 依次读取多个选项并汇总所有错误，最后由`finish`统一返回：
+/// ```no_run
+/// use custom_utils::args::ArgReader;
+/// let mut reader = ArgReader::new();
+/// let port: u16 = reader.required("--port", "-p");
+/// let dirs: Vec<String> = reader.values("--include", "-I");
+/// let verbose = reader.value_or("--level", "", 1u8);
+/// if let Err(e) = reader.finish() {
+///     eprintln!("{}", e);
+///     std::process::exit(2);
+/// }
+/// ```
+pub struct ArgReader {
+    args: Args,
+    errors: Vec<ArgError>,
+}
+
+impl Default for ArgReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArgReader {
+    pub fn new() -> Self {
+        Self::from_args(Args::from_env())
+    }
+    pub fn from_args(args: Args) -> Self {
+        Self {
+            args,
+            errors: Vec::new(),
+        }
+    }
+    pub fn args(&self) -> &Args {
+        &self.args
+    }
+    pub fn exist(&self, long: &str, short: &str) -> bool {
+        self.args.exist_arg(long, short)
+    }
+    pub fn value<T>(&mut self, long: &str, short: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let rs = self.args.arg_value_as(long, short);
+        self.record(rs).flatten()
+    }
+    /// 缺少时记录错误并返回`T::default()`，以便继续读取其他选项
+    pub fn required<T>(&mut self, long: &str, short: &str) -> T
+    where
+        T: FromStr + Default,
+        T::Err: Display,
+    {
+        let rs = self.args.arg_value_required(long, short);
+        self.record(rs).unwrap_or_default()
+    }
+    pub fn value_or<T>(&mut self, long: &str, short: &str, default: T) -> T
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.value(long, short).unwrap_or(default)
+    }
+    pub fn values<T>(&mut self, long: &str, short: &str) -> Vec<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let rs = self.args.arg_values_as(long, short);
+        self.record(rs).unwrap_or_default()
+    }
+    /// 已记录的错误
+    pub fn errors(&self) -> &[ArgError] {
+        &self.errors
+    }
+    pub fn finish(self) -> Result<(), ArgErrors> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ArgErrors(self.errors))
+        }
+    }
+    fn record<T>(&mut self, rs: Result<T, ArgError>) -> Option<T> {
+        match rs {
+            Ok(val) => Some(val),
+            Err(e) => {
+                self.errors.push(e);
+                None
+            }
         }
     }
-    None
 }
@@ -0,0 +1,162 @@
+use crate::util_args::ArgError;
+
+/// 规范化后的选项名
+#[derive(Debug, Clone)]
+pub(crate) struct OptName {
+    pub(crate) long: String,
+    pub(crate) short: Option<char>,
+}
+
+impl OptName {
+    /// `--config`/`config`与`-c`/`c`均可，`short`为空表示没有短选项
+    pub(crate) fn new(long: &str, short: &str) -> Result<Self, ArgError> {
+        let long = long.strip_prefix("--").unwrap_or(long);
+        if long.is_empty() || long.starts_with('-') || long.contains('=') {
+            return Err(ArgError::InvalidName(long.to_string()));
+        }
+        let short_name = short.strip_prefix('-').unwrap_or(short);
+        let mut chars = short_name.chars();
+        let short = match (chars.next(), chars.next()) {
+            (None, _) => None,
+            (Some(c), None) if c != '-' && c != '=' => Some(c),
+            _ => return Err(ArgError::InvalidName(short.to_string())),
+        };
+        Ok(Self {
+            long: long.to_string(),
+            short,
+        })
+    }
+    /// 用于错误提示，如`--config/-c`
+    pub(crate) fn display(&self) -> String {
+        match self.short {
+            Some(c) => format!("--{}/-{}", self.long, c),
+            None => format!("--{}", self.long),
+        }
+    }
+}
+
+/// 选项值的位置
+pub(crate) enum Found {
+    /// 下一个参数（下标）
+    Next(usize),
+    /// 与选项写在同一个参数中，如`--config=path`、`-cpath`
+    Attached(String),
+}
+
+/// 每次出现对应一项，选项在末尾且缺少值时为`None`
+pub(crate) fn find_values(
+    args: &[String],
+    name: &OptName,
+    value_shorts: Option<&[char]>,
+) -> Vec<Option<Found>> {
+    let mut values = Vec::new();
+    let next = |index: usize| (index + 1 < args.len()).then_some(Found::Next(index + 1));
+    let mut iter = args.iter().enumerate();
+    while let Some((index, arg)) = iter.next() {
+        if arg == "--" {
+            break;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            match long.split_once('=') {
+                Some((long, val)) if long == name.long => {
+                    values.push(Some(Found::Attached(val.to_string())))
+                }
+                None if long == name.long => {
+                    values.push(next(index));
+                    iter.next();
+                }
+                _ => {}
+            }
+        } else if let Some(cluster) = arg.strip_prefix('-') {
+            let Some(c) = name.short else {
+                continue;
+            };
+            match scan_cluster(cluster, c, value_shorts) {
+                Some("") => {
+                    values.push(next(index));
+                    iter.next();
+                }
+                Some(rest) if value_shorts.is_none_or(|x| x.contains(&c)) => {
+                    values.push(Some(Found::Attached(rest.to_string())))
+                }
+                // 已声明不需要值，是合并短选项中的开关
+                Some(_) => values.push(None),
+                None => {}
+            }
+        }
+    }
+    values
+}
+
+pub(crate) fn find_flag(args: &[String], name: &OptName, value_shorts: Option<&[char]>) -> bool {
+    for arg in args {
+        if arg == "--" {
+            break;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let long = long.split_once('=').map_or(long, |x| x.0);
+            if long == name.long {
+                return true;
+            }
+        } else if let Some(cluster) = arg.strip_prefix('-') {
+            if name
+                .short
+                .is_some_and(|c| scan_cluster(cluster, c, value_shorts).is_some())
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// 在合并短选项`cluster`（不含`-`）中查找`c`，返回其后的内容。
+/// 未声明`value_shorts`时只有第一个字符是选项，其后的内容都是它的值；
+/// 声明后从左到右读取，需要值的短选项之后的内容都是它的值，非字母数字之后不再有选项
+fn scan_cluster<'a>(cluster: &'a str, c: char, value_shorts: Option<&[char]>) -> Option<&'a str> {
+    let Some(value_shorts) = value_shorts else {
+        return cluster.strip_prefix(c);
+    };
+    for (pos, ch) in cluster.char_indices() {
+        if ch == c {
+            return Some(&cluster[pos + ch.len_utf8()..]);
+        }
+        if !ch.is_alphanumeric() || value_shorts.contains(&ch) {
+            return None;
+        }
+    }
+    None
+}
+
+pub(crate) fn find_command(args: &[String]) -> Option<String> {
+    match args.first()?.as_str() {
+        "--" => args.get(1).cloned(),
+        arg if arg.starts_with('-') && arg != "-" => None,
+        arg => Some(arg.to_string()),
+    }
+}
+
+/// 编辑距离最近的候选项，距离过大时返回`None`
+pub(crate) fn suggest<'a>(
+    input: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    candidates
+        .into_iter()
+        .map(|x| (distance(input, x), x))
+        .filter(|(d, x)| *d <= 2.max(x.len() / 3))
+        .min_by_key(|x| x.0)
+        .map(|x| x.1)
+}
+
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur.push((prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
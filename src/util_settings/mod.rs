@@ -0,0 +1,243 @@
+//! 分层配置：命令行选项 > 环境变量 > 配置文件 > 默认值
+//!
+//! 键名如`server.port`，对应：
+//!     命令行`--server-port`（可另设短选项）
+//!     环境变量`{PREFIX}_SERVER_PORT`，前缀默认为大写的app名，如`DNS_SERVER_PORT`
+//!     toml配置文件中的`[server] port = ...`，文件由`--config`/`-c`指定，
+//!         否则为/var/local/etc/{app}/config.toml（不存在时忽略）
+
+use crate::args::Args;
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// 生效值的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// 命令行选项，如`--server-port`
+    Cli(String),
+    /// 环境变量名
+    Env(String),
+    /// 配置文件路径
+    File(PathBuf),
+    Default,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Cli(flag) => write!(f, "cli {}", flag),
+            Source::Env(var) => write!(f, "env {}", var),
+            Source::File(path) => write!(f, "file {}", path.display()),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+struct Key {
+    name: String,
+    short: String,
+    default: Option<String>,
+}
+
+impl Key {
+    fn long(&self) -> String {
+        format!("--{}", self.name.replace(['.', '_'], "-"))
+    }
+    fn env(&self, prefix: &str) -> String {
+        format!("{}_{}", prefix, self.name.replace(['.', '-'], "_")).to_uppercase()
+    }
+}
+
+/// 声明配置项并解析出生效值
+/// ```no_run
+/// use custom_utils::settings::SettingsResolver;
+/// let settings = SettingsResolver::new("dns")
+///     .key("server.port", "-p", Some("53"))
+///     .key("log.level", "", Some("info"))
+///     .resolve()
+///     .unwrap();
+/// let port: u16 = settings.get_as("server.port").unwrap().unwrap();
+/// println!("{}", settings.dump());
+/// ```
+pub struct SettingsResolver {
+    app: String,
+    env_prefix: String,
+    args: Option<Args>,
+    config_file: Option<PathBuf>,
+    keys: Vec<Key>,
+}
+
+impl SettingsResolver {
+    pub fn new(app: &str) -> Self {
+        Self {
+            app: app.to_string(),
+            env_prefix: app.replace('-', "_").to_uppercase(),
+            args: None,
+            config_file: None,
+            keys: Vec::new(),
+        }
+    }
+    /// 环境变量前缀，默认为大写的app名
+    pub fn env_prefix(mut self, prefix: &str) -> Self {
+        self.env_prefix = prefix.trim_end_matches('_').to_string();
+        self
+    }
+    /// 默认读取当前进程的参数
+    pub fn args(mut self, args: Args) -> Self {
+        self.args = Some(args);
+        self
+    }
+    /// 指定配置文件，优先于`--config`
+    pub fn config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+    /// 声明配置项，`short`为空表示没有短选项
+    pub fn key(mut self, name: &str, short: &str, default: Option<&str>) -> Self {
+        self.keys.push(Key {
+            name: name.to_string(),
+            short: short.to_string(),
+            default: default.map(|x| x.to_string()),
+        });
+        self
+    }
+
+    pub fn resolve(self) -> Result<Settings> {
+        let args = self.args.unwrap_or_else(Args::from_env);
+        let (path, required) = match self
+            .config_file
+            .or_else(|| args.arg_value_os("--config", "-c").map(PathBuf::from))
+        {
+            Some(path) => (path, true),
+            None => (
+                PathBuf::from("/var/local/etc")
+                    .join(&self.app)
+                    .join("config.toml"),
+                false,
+            ),
+        };
+        let table = load_table(&path, required)?;
+        let mut values = BTreeMap::new();
+        for key in &self.keys {
+            let long = key.long();
+            let env = key.env(&self.env_prefix);
+            let value = if let Some(val) = args.arg_value(&long, &key.short) {
+                Some((val, Source::Cli(long)))
+            } else if let Ok(val) = std::env::var(&env) {
+                Some((val, Source::Env(env)))
+            } else if let Some(val) = table.as_ref().and_then(|x| lookup(x, &key.name)) {
+                Some((val, Source::File(path.clone())))
+            } else {
+                key.default.clone().map(|val| (val, Source::Default))
+            };
+            values.insert(key.name.clone(), value);
+        }
+        Ok(Settings {
+            config_file: table.map(|_| path),
+            values,
+        })
+    }
+}
+
+fn load_table(path: &Path, required: bool) -> Result<Option<toml::Table>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("read config file {:?}", path)),
+    };
+    let table = text
+        .parse::<toml::Table>()
+        .with_context(|| format!("parse config file {:?}", path))?;
+    Ok(Some(table))
+}
+
+/// 按`a.b.c`查找，字符串以外的值转为toml文本
+fn lookup(table: &toml::Table, name: &str) -> Option<String> {
+    let mut parts = name.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_table()?.get(part)?;
+    }
+    Some(match value {
+        toml::Value::String(val) => val.clone(),
+        val => val.to_string(),
+    })
+}
+
+/// 解析后的生效配置
+#[derive(Debug, Clone)]
+pub struct Settings {
+    config_file: Option<PathBuf>,
+    values: BTreeMap<String, Option<(String, Source)>>,
+}
+
+impl Settings {
+    /// 实际读取的配置文件
+    pub fn config_file(&self) -> Option<&Path> {
+        self.config_file.as_deref()
+    }
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entry(name).map(|x| x.0.as_str())
+    }
+    pub fn source(&self, name: &str) -> Option<&Source> {
+        self.entry(name).map(|x| &x.1)
+    }
+    /// 未设置时返回`Ok(None)`，无法解析时的错误包含键名与来源
+    pub fn get_as<T>(&self, name: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.entry(name) {
+            Some((val, source)) => val.parse().map(Some).map_err(|e: T::Err| {
+                anyhow!(
+                    "invalid value '{}' for {} from {}: {}",
+                    val,
+                    name,
+                    source,
+                    e
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+    pub fn required<T>(&self, name: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.get_as(name)? {
+            Some(val) => Ok(val),
+            None => bail!("missing required setting {}", name),
+        }
+    }
+    /// 每行一项：`name = value (source)`
+    pub fn dump(&self) -> String {
+        let width = self.values.keys().map(|x| x.len()).max().unwrap_or(0);
+        let mut text = String::new();
+        if let Some(path) = &self.config_file {
+            text.push_str(&format!("# config file {}\n", path.display()));
+        }
+        for (name, value) in &self.values {
+            match value {
+                Some((val, source)) => {
+                    text.push_str(&format!("{:width$} = {} ({})\n", name, val, source))
+                }
+                None => text.push_str(&format!("{:width$} = <unset>\n", name)),
+            }
+        }
+        text
+    }
+    fn entry(&self, name: &str) -> Option<&(String, Source)> {
+        self.values.get(name)?.as_ref()
+    }
+}
+
+impl Display for Settings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.dump())
+    }
+}
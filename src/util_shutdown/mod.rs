@@ -0,0 +1,248 @@
+use log::{debug, error, info, warn};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// 优雅退出协调器：监听SIGINT/SIGTERM，分发[`ShutdownToken`]，
+/// 并在退出时等待已登记的任务结束
+///
+/// ```ignore
+/// let shutdown = Shutdown::new();
+/// let guard = shutdown.register("worker");
+/// tokio::spawn(async move {
+///     let token = guard.token();
+///     loop {
+///         tokio::select! {
+///             _ = token.cancelled() => break,
+///             _ = do_work() => {}
+///         }
+///     }
+/// });
+/// shutdown.listen().await;
+/// let unfinished = shutdown.drain(Duration::from_secs(10)).await;
+/// ```
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+/// 可克隆的退出信号，异步任务`cancelled().await`，同步线程`wait`/`is_shutdown`
+#[derive(Clone)]
+pub struct ShutdownToken {
+    inner: Arc<Inner>,
+}
+
+/// 已登记任务的凭证，drop即视为任务已结束
+pub struct TaskGuard {
+    inner: Arc<Inner>,
+    id: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    state: Mutex<State>,
+    changed: Condvar,
+    changed_async: Notify,
+}
+
+#[derive(Default)]
+struct State {
+    triggered: bool,
+    next_id: u64,
+    tasks: BTreeMap<u64, String>,
+}
+
+impl Inner {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+    fn notify(&self) {
+        self.changed.notify_all();
+        self.changed_async.notify_waiters();
+    }
+    /// 异步等待，直到`done`返回true或超时（返回false）
+    async fn wait_until(&self, done: impl Fn(&State) -> bool, deadline: Option<Instant>) -> bool {
+        loop {
+            let notified = self.changed_async.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if done(&self.lock()) {
+                return true;
+            }
+            match deadline {
+                Some(deadline) => {
+                    if tokio::time::timeout_at(deadline.into(), notified)
+                        .await
+                        .is_err()
+                    {
+                        return done(&self.lock());
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+    fn wait_until_blocking(
+        &self,
+        done: impl Fn(&State) -> bool,
+        timeout: Option<Duration>,
+    ) -> bool {
+        let state = self.lock();
+        match timeout {
+            Some(timeout) => {
+                let (state, _) = self
+                    .changed
+                    .wait_timeout_while(state, timeout, |x| !done(x))
+                    .unwrap();
+                done(&state)
+            }
+            None => {
+                let _state = self.changed.wait_while(state, |x| !done(x)).unwrap();
+                true
+            }
+        }
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn token(&self) -> ShutdownToken {
+        ShutdownToken {
+            inner: self.inner.clone(),
+        }
+    }
+    /// 登记需要在退出前结束的任务
+    pub fn register(&self, name: impl Into<String>) -> TaskGuard {
+        let mut state = self.inner.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.tasks.insert(id, name.into());
+        TaskGuard {
+            inner: self.inner.clone(),
+            id,
+        }
+    }
+    /// 手动触发退出
+    pub fn trigger(&self) {
+        let mut state = self.inner.lock();
+        if !state.triggered {
+            state.triggered = true;
+            debug!("shutdown triggered");
+            self.inner.notify();
+        }
+    }
+    pub fn is_shutdown(&self) -> bool {
+        self.inner.lock().triggered
+    }
+    /// 尚未结束的任务名
+    pub fn pending_tasks(&self) -> Vec<String> {
+        self.inner.lock().tasks.values().cloned().collect()
+    }
+    /// 等待SIGINT/SIGTERM（或手动`trigger`），然后触发退出
+    pub async fn listen(&self) {
+        let token = self.token();
+        tokio::select! {
+            rs = wait_signal() => match rs {
+                Ok(signal) => info!("receive {}, shutting down", signal),
+                Err(e) => error!("fail to listen signal: {:?}", e),
+            },
+            _ = token.cancelled() => {}
+        }
+        self.trigger();
+    }
+    /// 供同步程序使用：在后台线程中监听信号
+    pub fn listen_in_thread(&self) -> std::io::Result<std::thread::JoinHandle<()>> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let shutdown = self.clone();
+        std::thread::Builder::new()
+            .name("shutdown-signal".to_string())
+            .spawn(move || runtime.block_on(shutdown.listen()))
+    }
+    /// 触发退出并等待已登记的任务结束，返回超时仍未结束的任务名
+    pub async fn drain(&self, deadline: Duration) -> Vec<String> {
+        self.trigger();
+        self.inner
+            .wait_until(|x| x.tasks.is_empty(), Some(Instant::now() + deadline))
+            .await;
+        self.finish(deadline)
+    }
+    pub fn drain_blocking(&self, deadline: Duration) -> Vec<String> {
+        self.trigger();
+        self.inner
+            .wait_until_blocking(|x| x.tasks.is_empty(), Some(deadline));
+        self.finish(deadline)
+    }
+    fn finish(&self, deadline: Duration) -> Vec<String> {
+        let unfinished = self.pending_tasks();
+        if unfinished.is_empty() {
+            info!("all tasks stopped");
+        } else {
+            warn!(
+                "tasks failed to stop in {:?}: {}",
+                deadline,
+                unfinished.join(", ")
+            );
+        }
+        log::logger().flush();
+        unfinished
+    }
+}
+
+impl ShutdownToken {
+    pub fn is_shutdown(&self) -> bool {
+        self.inner.lock().triggered
+    }
+    /// 等待退出信号，可用于`tokio::select!`
+    pub async fn cancelled(&self) {
+        self.inner.wait_until(|x| x.triggered, None).await;
+    }
+    /// 阻塞等待退出信号
+    pub fn wait(&self) {
+        self.inner.wait_until_blocking(|x| x.triggered, None);
+    }
+    /// 阻塞等待退出信号，超时返回false；可替代同步循环中的`sleep`
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        self.inner
+            .wait_until_blocking(|x| x.triggered, Some(timeout))
+    }
+}
+
+impl TaskGuard {
+    pub fn token(&self) -> ShutdownToken {
+        ShutdownToken {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        let mut state = self.inner.lock();
+        if let Some(name) = state.tasks.remove(&self.id) {
+            debug!("task [{}] stopped", name);
+        }
+        self.inner.notify();
+    }
+}
+
+#[cfg(unix)]
+async fn wait_signal() -> std::io::Result<&'static str> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut sigint = signal(SignalKind::interrupt())?;
+    let mut sigterm = signal(SignalKind::terminate())?;
+    tokio::select! {
+        _ = sigint.recv() => Ok("SIGINT"),
+        _ = sigterm.recv() => Ok("SIGTERM"),
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_signal() -> std::io::Result<&'static str> {
+    tokio::signal::ctrl_c().await?;
+    Ok("ctrl-c")
+}
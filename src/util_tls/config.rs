@@ -0,0 +1,227 @@
+use crate::util_tls::{init_root_certs_by_native, Passphrase, PemSource, ReloadingCertResolver};
+use anyhow::{bail, Context, Result};
+use rustls::server::{AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient};
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig, SignatureScheme};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 由证书链与私钥文件构造`ServerConfig`，默认使用rustls的安全参数（TLS1.2/1.3）：
+/// ```no_run
+/// use custom_utils::tls::ServerTlsBuilder;
+/// let config = ServerTlsBuilder::new("./certs/server.crt", "./certs/server.key")
+///     .client_ca("./certs/ca.crt")
+///     .alpn(&["h2", "http/1.1"])
+///     .build()
+///     .unwrap();
+/// ```
+pub struct ServerTlsBuilder {
+    cert_chain: PemSource,
+    key: PemSource,
+    client_ca: Option<PemSource>,
+    client_auth_optional: bool,
+    passphrase: Option<Passphrase>,
+    alpn: Vec<Vec<u8>>,
+    reload_interval: Option<Duration>,
+}
+
+impl ServerTlsBuilder {
+    pub fn new(cert_chain: impl AsRef<Path>, key: impl AsRef<Path>) -> Self {
+        Self::from_sources(cert_chain.as_ref().into(), key.as_ref().into())
+    }
+    /// 证书链与私钥来自内存、环境变量或systemd凭据
+    pub fn from_sources(cert_chain: PemSource, key: PemSource) -> Self {
+        Self {
+            cert_chain,
+            key,
+            client_ca: None,
+            client_auth_optional: false,
+            passphrase: None,
+            alpn: Vec::new(),
+            reload_interval: None,
+        }
+    }
+    /// 用该CA校验客户端证书（mTLS），默认要求客户端提供证书
+    pub fn client_ca(self, path: impl AsRef<Path>) -> Self {
+        self.client_ca_source(path.as_ref().into())
+    }
+    pub fn client_ca_source(mut self, source: PemSource) -> Self {
+        self.client_ca = Some(source);
+        self
+    }
+    /// 允许客户端不提供证书，提供时仍需通过校验
+    pub fn client_auth_optional(mut self) -> Self {
+        self.client_auth_optional = true;
+        self
+    }
+    /// 私钥加密时的口令来源
+    pub fn passphrase(mut self, passphrase: Passphrase) -> Self {
+        self.passphrase = Some(passphrase);
+        self
+    }
+    /// 按优先级排列的ALPN协议，如`h2`、`http/1.1`
+    pub fn alpn(mut self, protocols: &[&str]) -> Self {
+        self.alpn = protocols.iter().map(|x| x.as_bytes().to_vec()).collect();
+        self
+    }
+    /// 每隔`interval`检查证书链与私钥文件，更新后自动换用，见`ReloadingCertResolver`。
+    /// 仅支持文件来源
+    pub fn reload_every(mut self, interval: Duration) -> Self {
+        self.reload_interval = Some(interval);
+        self
+    }
+    pub fn build(self) -> Result<ServerConfig> {
+        let builder = ServerConfig::builder().with_safe_defaults();
+        let builder = match &self.client_ca {
+            Some(source) => {
+                let roots = source
+                    .root_certs()
+                    .with_context(|| format!("load client ca {}", source))?;
+                if self.client_auth_optional {
+                    builder.with_client_cert_verifier(AllowAnyAnonymousOrAuthenticatedClient::new(
+                        roots,
+                    ))
+                } else {
+                    builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots))
+                }
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let mut config = match (self.reload_interval, &self.cert_chain, &self.key) {
+            (Some(interval), PemSource::File(cert_chain), PemSource::File(key)) => {
+                let resolver =
+                    ReloadingCertResolver::load_new(cert_chain, key, self.passphrase.clone())?;
+                resolver.watch(interval);
+                builder.with_cert_resolver(Arc::new(resolver))
+            }
+            (Some(_), _, _) => bail!("certificate reloading requires file sources"),
+            (None, _, _) => {
+                let (certs, key) =
+                    load_cert_and_key(&self.cert_chain, &self.key, self.passphrase.as_ref())?;
+                builder.with_single_cert(certs, key)?
+            }
+        };
+        config.alpn_protocols = self.alpn;
+        Ok(config)
+    }
+}
+
+/// 构造`ClientConfig`，默认信任系统根证书：
+/// ```no_run
+/// use custom_utils::tls::ClientTlsBuilder;
+/// let config = ClientTlsBuilder::new()
+///     .root_ca("./certs/ca.crt")
+///     .client_cert("./certs/client.crt", "./certs/client.key")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct ClientTlsBuilder {
+    root_ca: Option<PemSource>,
+    client_cert: Option<(PemSource, PemSource)>,
+    passphrase: Option<Passphrase>,
+    alpn: Vec<Vec<u8>>,
+}
+
+impl ClientTlsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// 只信任该文件中的根证书，而不是系统根证书
+    pub fn root_ca(self, path: impl AsRef<Path>) -> Self {
+        self.root_ca_source(path.as_ref().into())
+    }
+    pub fn root_ca_source(mut self, source: PemSource) -> Self {
+        self.root_ca = Some(source);
+        self
+    }
+    /// 客户端证书链与私钥（mTLS）
+    pub fn client_cert(self, cert_chain: impl AsRef<Path>, key: impl AsRef<Path>) -> Self {
+        self.client_cert_source(cert_chain.as_ref().into(), key.as_ref().into())
+    }
+    pub fn client_cert_source(mut self, cert_chain: PemSource, key: PemSource) -> Self {
+        self.client_cert = Some((cert_chain, key));
+        self
+    }
+    /// 客户端私钥加密时的口令来源
+    pub fn passphrase(mut self, passphrase: Passphrase) -> Self {
+        self.passphrase = Some(passphrase);
+        self
+    }
+    pub fn alpn(mut self, protocols: &[&str]) -> Self {
+        self.alpn = protocols.iter().map(|x| x.as_bytes().to_vec()).collect();
+        self
+    }
+    pub fn build(self) -> Result<ClientConfig> {
+        let roots: RootCertStore = match &self.root_ca {
+            Some(source) => source
+                .root_certs()
+                .with_context(|| format!("load root ca {}", source))?,
+            None => init_root_certs_by_native()?,
+        };
+        let builder = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots);
+        let mut config = match &self.client_cert {
+            Some((cert_chain, key)) => {
+                let (certs, key) = load_cert_and_key(cert_chain, key, self.passphrase.as_ref())?;
+                builder.with_single_cert(certs, key)?
+            }
+            None => builder.with_no_client_auth(),
+        };
+        config.alpn_protocols = self.alpn;
+        Ok(config)
+    }
+}
+
+/// 读取证书链与私钥，并确认私钥与叶子证书匹配
+pub(crate) fn load_cert_and_key(
+    cert_chain: &PemSource,
+    key: &PemSource,
+    passphrase: Option<&Passphrase>,
+) -> Result<(Vec<Certificate>, PrivateKey)> {
+    let certs = cert_chain
+        .certs()
+        .with_context(|| format!("load certificate chain {}", cert_chain))?;
+    let private_key = match passphrase {
+        Some(passphrase) => key.private_key_with_passphrase(passphrase)?,
+        None => key.private_key()?,
+    };
+    verify_key_matches(&certs[0], &private_key).with_context(|| {
+        format!(
+            "private key {} does not match the leaf certificate in {}",
+            key, cert_chain
+        )
+    })?;
+    Ok((certs, private_key))
+}
+
+/// 用私钥签名，再用叶子证书中的公钥验签
+pub fn verify_key_matches(leaf: &Certificate, key: &PrivateKey) -> Result<()> {
+    const SCHEMES: [SignatureScheme; 4] = [
+        SignatureScheme::RSA_PKCS1_SHA256,
+        SignatureScheme::ECDSA_NISTP256_SHA256,
+        SignatureScheme::ECDSA_NISTP384_SHA384,
+        SignatureScheme::ED25519,
+    ];
+    let signing_key =
+        rustls::sign::any_supported_type(key).context("unsupported private key type")?;
+    let Some(signer) = signing_key.choose_scheme(&SCHEMES) else {
+        bail!(
+            "unsupported private key algorithm {:?}",
+            signing_key.algorithm()
+        );
+    };
+    let message = b"custom-utils key check";
+    let signature = signer.sign(message)?;
+    let algorithm = match signer.scheme() {
+        SignatureScheme::RSA_PKCS1_SHA256 => &webpki::RSA_PKCS1_2048_8192_SHA256,
+        SignatureScheme::ECDSA_NISTP256_SHA256 => &webpki::ECDSA_P256_SHA256,
+        SignatureScheme::ECDSA_NISTP384_SHA384 => &webpki::ECDSA_P384_SHA384,
+        _ => &webpki::ED25519,
+    };
+    let cert = webpki::EndEntityCert::try_from(leaf.0.as_slice())
+        .map_err(|e| anyhow::anyhow!("invalid leaf certificate: {:?}", e))?;
+    cert.verify_signature(algorithm, message, &signature)
+        .map_err(|e| anyhow::anyhow!("signature check failed: {:?}", e))
+}
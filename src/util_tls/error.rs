@@ -0,0 +1,71 @@
+use std::fmt::{Display, Formatter};
+
+/// 读取私钥失败的原因，`source`为来源描述，如文件路径
+#[derive(Debug)]
+pub enum KeyError {
+    Io {
+        source: String,
+        error: std::io::Error,
+    },
+    /// 内容不是合法的PEM
+    InvalidPem { source: String },
+    /// 私钥已加密，需要提供口令
+    Encrypted { source: String },
+    /// OpenSSL传统格式（`Proc-Type: 4,ENCRYPTED`）的加密私钥，不支持
+    LegacyEncrypted { source: String },
+    /// 无法取得口令
+    Passphrase { source: String, reason: String },
+    /// 解密失败，通常是口令错误
+    Decrypt { source: String, reason: String },
+    /// 没有可用的私钥，`found`为其中的PEM类型，如`CERTIFICATE`
+    NotFound { source: String, found: Vec<String> },
+}
+
+impl Display for KeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyError::Io { source, error } => write!(f, "failed to read {}: {}", source, error),
+            KeyError::InvalidPem { source } => write!(f, "{} is not a valid PEM file", source),
+            KeyError::Encrypted { source } => write!(
+                f,
+                "private key in {} is encrypted, a passphrase is required",
+                source
+            ),
+            KeyError::LegacyEncrypted { source } => write!(
+                f,
+                "unsupported legacy encrypted PEM in {}, convert it with `openssl pkcs8 -topk8`",
+                source
+            ),
+            KeyError::Passphrase { source, reason } => {
+                write!(f, "failed to get the passphrase for {}: {}", source, reason)
+            }
+            KeyError::Decrypt { source, reason } => write!(
+                f,
+                "failed to decrypt private key in {} (wrong passphrase?): {}",
+                source, reason
+            ),
+            KeyError::NotFound { source, found } if found.is_empty() => {
+                write!(
+                    f,
+                    "no private key in {}: the file contains no PEM items",
+                    source
+                )
+            }
+            KeyError::NotFound { source, found } => write!(
+                f,
+                "no private key in {}: found only {}",
+                source,
+                found.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KeyError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -1,22 +1,129 @@
+use crate::util_tls::{KeyError, Passphrase, PemSource};
 use anyhow::{bail, Result};
+use rustls_pemfile::Item;
 use std::path::Path;
 
 pub fn load_rsa_key(path: impl AsRef<Path>) -> Result<rustls::PrivateKey> {
     let file = std::fs::File::open(path)?;
     let mut reader = std::io::BufReader::new(file);
     let datas = rustls_pemfile::rsa_private_keys(&mut reader)?;
-    for data in datas.into_iter() {
-        return Ok(rustls::PrivateKey(data));
+    match datas.into_iter().next() {
+        Some(data) => Ok(rustls::PrivateKey(data)),
+        None => bail!("未找到秘钥"),
     }
-    bail!("未找到秘钥")
 }
 
 pub fn load_pkcs8_key(path: impl AsRef<Path>) -> Result<rustls::PrivateKey> {
     let file = std::fs::File::open(path)?;
     let mut reader = std::io::BufReader::new(file);
     let datas = rustls_pemfile::pkcs8_private_keys(&mut reader)?;
-    for data in datas.into_iter() {
-        return Ok(rustls::PrivateKey(data));
+    match datas.into_iter().next() {
+        Some(data) => Ok(rustls::PrivateKey(data)),
+        None => bail!("未找到秘钥"),
     }
-    bail!("未找到秘钥")
+}
+
+/// 读取PEM文件中的第一个私钥，支持PKCS#1（RSA）、PKCS#8与SEC1（EC）
+pub fn load_private_key(path: impl AsRef<Path>) -> Result<rustls::PrivateKey, KeyError> {
+    PemSource::from(path.as_ref()).private_key()
+}
+
+/// 同`load_private_key`，另支持`ENCRYPTED PRIVATE KEY`（加密的PKCS#8），
+/// 仅在私钥加密时读取口令
+pub fn load_private_key_with_passphrase(
+    path: impl AsRef<Path>,
+    passphrase: &Passphrase,
+) -> Result<rustls::PrivateKey, KeyError> {
+    PemSource::from(path.as_ref()).private_key_with_passphrase(passphrase)
+}
+
+pub(crate) fn decrypt_private_key(
+    data: &[u8],
+    source: String,
+    passphrase: &Passphrase,
+) -> Result<rustls::PrivateKey, KeyError> {
+    let text = String::from_utf8_lossy(data);
+    let block = match pem_block(&text, "ENCRYPTED PRIVATE KEY") {
+        Some(block) => block,
+        None if text.contains("Proc-Type: 4,ENCRYPTED") => {
+            return Err(KeyError::LegacyEncrypted { source })
+        }
+        None => {
+            return Err(KeyError::Decrypt {
+                source,
+                reason: "only PKCS#8 `ENCRYPTED PRIVATE KEY` is supported".to_string(),
+            })
+        }
+    };
+    let decrypt_err = |e: &dyn std::fmt::Display| KeyError::Decrypt {
+        source: source.clone(),
+        reason: e.to_string(),
+    };
+    let (_, der) = pkcs8::der::pem::decode_vec(block.as_bytes()).map_err(|e| decrypt_err(&e))?;
+    let info =
+        pkcs8::EncryptedPrivateKeyInfo::try_from(der.as_slice()).map_err(|e| decrypt_err(&e))?;
+    let passphrase = passphrase.resolve().map_err(|e| KeyError::Passphrase {
+        source: source.clone(),
+        reason: format!("{:#}", e),
+    })?;
+    let key = info.decrypt(passphrase).map_err(|e| decrypt_err(&e))?;
+    Ok(rustls::PrivateKey(key.as_bytes().to_vec()))
+}
+
+/// 文本中第一个`label`类型的PEM块
+fn pem_block(text: &str, label: &str) -> Option<String> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let start = text.find(&begin)?;
+    let stop = text[start..].find(&end)? + start + end.len();
+    Some(text[start..stop].to_string())
+}
+
+pub(crate) fn parse_private_key(
+    data: &[u8],
+    source: String,
+) -> Result<rustls::PrivateKey, KeyError> {
+    let labels = pem_labels(data);
+    let text = String::from_utf8_lossy(data);
+    let legacy = text.contains("Proc-Type: 4,ENCRYPTED");
+    let encrypted = labels.iter().any(|x| x == "ENCRYPTED PRIVATE KEY");
+    let items = match rustls_pemfile::read_all(&mut std::io::BufReader::new(data)) {
+        Ok(items) => items,
+        Err(_) if encrypted => return Err(KeyError::Encrypted { source }),
+        Err(_) if legacy => return Err(KeyError::LegacyEncrypted { source }),
+        Err(_) => return Err(KeyError::InvalidPem { source }),
+    };
+    for item in items {
+        match item {
+            Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key) => {
+                return Ok(rustls::PrivateKey(key))
+            }
+            _ => {}
+        }
+    }
+    if encrypted {
+        return Err(KeyError::Encrypted { source });
+    }
+    if legacy {
+        return Err(KeyError::LegacyEncrypted { source });
+    }
+    Err(KeyError::NotFound {
+        source,
+        found: labels,
+    })
+}
+
+/// PEM块的类型，如`CERTIFICATE`，重复的类型只保留一个
+fn pem_labels(data: &[u8]) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for line in String::from_utf8_lossy(data).lines() {
+        let label = line
+            .trim()
+            .strip_prefix("-----BEGIN ")
+            .and_then(|x| x.strip_suffix("-----"));
+        if let Some(label) = label.filter(|x| !labels.iter().any(|l| l == x)) {
+            labels.push(label.to_string());
+        }
+    }
+    labels
 }
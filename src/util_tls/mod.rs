@@ -1,5 +1,15 @@
 mod certs;
+mod config;
+mod error;
 mod keys;
+mod passphrase;
+mod reload;
+mod source;
 
 pub use certs::*;
+pub use config::*;
+pub use error::*;
 pub use keys::*;
+pub use passphrase::*;
+pub use reload::*;
+pub use source::*;
@@ -0,0 +1,61 @@
+use anyhow::{Context, Result};
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+type PassphraseFn = dyn Fn() -> Result<String> + Send + Sync;
+
+/// 加密私钥的口令来源
+#[derive(Clone)]
+pub enum Passphrase {
+    Value(String),
+    /// 环境变量名
+    Env(String),
+    /// 文件内容，忽略末尾的换行
+    File(PathBuf),
+    Callback(Arc<PassphraseFn>),
+}
+
+impl Passphrase {
+    pub fn value(passphrase: &str) -> Self {
+        Passphrase::Value(passphrase.to_string())
+    }
+    pub fn env(name: &str) -> Self {
+        Passphrase::Env(name.to_string())
+    }
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Passphrase::File(path.into())
+    }
+    pub fn callback<F>(f: F) -> Self
+    where
+        F: Fn() -> Result<String> + Send + Sync + 'static,
+    {
+        Passphrase::Callback(Arc::new(f))
+    }
+    pub fn resolve(&self) -> Result<String> {
+        match self {
+            Passphrase::Value(value) => Ok(value.clone()),
+            Passphrase::Env(name) => {
+                std::env::var(name).with_context(|| format!("read passphrase from env {}", name))
+            }
+            Passphrase::File(path) => {
+                let text = std::fs::read_to_string(path)
+                    .with_context(|| format!("read passphrase from {}", path.display()))?;
+                Ok(text.trim_end_matches(['\r', '\n']).to_string())
+            }
+            Passphrase::Callback(f) => f(),
+        }
+    }
+}
+
+/// 不输出口令本身
+impl Debug for Passphrase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Passphrase::Value(_) => write!(f, "Passphrase::Value(***)"),
+            Passphrase::Env(name) => write!(f, "Passphrase::Env({:?})", name),
+            Passphrase::File(path) => write!(f, "Passphrase::File({:?})", path),
+            Passphrase::Callback(_) => write!(f, "Passphrase::Callback"),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::util_tls::{load_cert_and_key, Passphrase};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls::Certificate;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// 监视证书链与私钥文件的`ResolvesServerCert`，文件更新后换用新证书，
+/// 新文件无效（无法解析、私钥不匹配）时继续使用原证书：
+/// ```no_run
+/// use custom_utils::tls::ReloadingCertResolver;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// let resolver = ReloadingCertResolver::new("./certs/server.crt", "./certs/server.key").unwrap();
+/// resolver.watch(Duration::from_secs(60));
+/// let config = rustls::ServerConfig::builder()
+///     .with_safe_defaults()
+///     .with_no_client_auth()
+///     .with_cert_resolver(Arc::new(resolver));
+/// ```
+#[derive(Clone)]
+pub struct ReloadingCertResolver {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    cert_chain: PathBuf,
+    key: PathBuf,
+    passphrase: Option<Passphrase>,
+    current: RwLock<Arc<CertifiedKey>>,
+    /// 最近一次成功加载时证书链与私钥文件的修改时间
+    loaded: Mutex<Modified>,
+    /// 最近一次加载失败时的修改时间，同一组文件只警告一次
+    failed: Mutex<Option<Modified>>,
+}
+
+type Modified = (Option<SystemTime>, Option<SystemTime>);
+
+impl Inner {
+    fn modified(&self) -> Modified {
+        (modified_time(&self.cert_chain), modified_time(&self.key))
+    }
+}
+
+impl ReloadingCertResolver {
+    /// 立即加载一次，失败时报错
+    pub fn new(cert_chain: impl AsRef<Path>, key: impl AsRef<Path>) -> Result<Self> {
+        Self::load_new(cert_chain.as_ref(), key.as_ref(), None)
+    }
+    pub fn with_passphrase(
+        cert_chain: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+        passphrase: Passphrase,
+    ) -> Result<Self> {
+        Self::load_new(cert_chain.as_ref(), key.as_ref(), Some(passphrase))
+    }
+    pub(crate) fn load_new(
+        cert_chain: &Path,
+        key: &Path,
+        passphrase: Option<Passphrase>,
+    ) -> Result<Self> {
+        let modified: Modified = (modified_time(cert_chain), modified_time(key));
+        let certified = load_certified_key(cert_chain, key, passphrase.as_ref())?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                cert_chain: cert_chain.to_path_buf(),
+                key: key.to_path_buf(),
+                passphrase,
+                current: RwLock::new(certified),
+                loaded: Mutex::new(modified),
+                failed: Mutex::new(None),
+            }),
+        })
+    }
+
+    /// 当前使用的证书链与私钥
+    pub fn current(&self) -> Arc<CertifiedKey> {
+        self.inner.current.read().unwrap().clone()
+    }
+
+    /// 无条件重新加载，失败时保留原证书并返回错误
+    pub fn reload(&self) -> Result<()> {
+        let inner = &self.inner;
+        let modified = inner.modified();
+        let certified =
+            load_certified_key(&inner.cert_chain, &inner.key, inner.passphrase.as_ref())?;
+        *inner.current.write().unwrap() = certified;
+        *inner.loaded.lock().unwrap() = modified;
+        Ok(())
+    }
+
+    /// 任一文件的修改时间与上次成功加载时不同就重新加载，返回是否换用了新证书。
+    /// 失败后每次调用都会重试（文件可能仍在写入），但同一组修改时间只警告一次
+    pub fn reload_if_changed(&self) -> bool {
+        let inner = &self.inner;
+        let modified = inner.modified();
+        if *inner.loaded.lock().unwrap() == modified {
+            return false;
+        }
+        match self.reload() {
+            Ok(()) => {
+                *inner.failed.lock().unwrap() = None;
+                true
+            }
+            Err(e) => {
+                let mut failed = inner.failed.lock().unwrap();
+                if *failed != Some(modified) {
+                    *failed = Some(modified);
+                    warn!(
+                        "keep the current certificate, fail to reload {:?}: {:#}",
+                        inner.cert_chain, e
+                    );
+                }
+                false
+            }
+        }
+    }
+
+    /// 后台线程每隔`interval`检查一次文件，resolver全部drop后线程退出
+    pub fn watch(&self, interval: Duration) {
+        let weak = Arc::downgrade(&self.inner);
+        std::thread::Builder::new()
+            .name("tls-cert-reload".to_string())
+            .spawn(move || loop {
+                std::thread::sleep(interval);
+                match weak.upgrade() {
+                    Some(inner) => {
+                        ReloadingCertResolver { inner }.reload_if_changed();
+                    }
+                    None => break,
+                }
+            })
+            .expect("fail to spawn tls cert reload");
+    }
+}
+
+impl ResolvesServerCert for ReloadingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+fn load_certified_key(
+    cert_chain: &Path,
+    key: &Path,
+    passphrase: Option<&Passphrase>,
+) -> Result<Arc<CertifiedKey>> {
+    let (certs, private_key) = load_cert_and_key(&cert_chain.into(), &key.into(), passphrase)?;
+    let signing_key =
+        rustls::sign::any_supported_type(&private_key).context("unsupported private key type")?;
+    log_expiry(cert_chain, &certs[0]);
+    Ok(Arc::new(CertifiedKey::new(certs, signing_key)))
+}
+
+/// 记录叶子证书的有效期，已过期或尚未生效时输出警告
+fn log_expiry(cert_chain: &Path, leaf: &Certificate) {
+    match x509_parser::parse_x509_certificate(&leaf.0) {
+        Ok((_, cert)) => {
+            let validity = cert.validity();
+            let not_after = validity.not_after.to_rfc2822();
+            if validity.is_valid() {
+                info!(
+                    "load certificate {:?}, expires at {}",
+                    cert_chain, not_after
+                );
+            } else {
+                warn!(
+                    "load certificate {:?}, not valid now (valid from {} to {})",
+                    cert_chain,
+                    validity.not_before.to_rfc2822(),
+                    not_after
+                );
+            }
+        }
+        Err(e) => warn!("fail to parse the expiry of {:?}: {}", cert_chain, e),
+    }
+}
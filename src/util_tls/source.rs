@@ -0,0 +1,159 @@
+use crate::util_tls::{decrypt_private_key, parse_private_key, KeyError, Passphrase};
+use anyhow::{bail, Result};
+use base64ct::{Base64, Encoding};
+use rustls::{Certificate, PrivateKey, RootCertStore};
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// 证书或私钥（PEM）的来源，无需先落盘：
+/// ```no_run
+/// use custom_utils::tls::{PemSource, ServerTlsBuilder};
+/// let config = ServerTlsBuilder::from_sources(
+///     PemSource::env("TLS_CERT"),
+///     PemSource::credential("server.key"),
+/// )
+/// .build()
+/// .unwrap();
+/// ```
+#[derive(Clone)]
+pub enum PemSource {
+    File(PathBuf),
+    Bytes(Vec<u8>),
+    /// 环境变量名，值为PEM文本或其base64编码
+    Env(String),
+    /// systemd凭据名，即`$CREDENTIALS_DIRECTORY/{name}`
+    Credential(String),
+}
+
+impl PemSource {
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        PemSource::File(path.into())
+    }
+    pub fn bytes(data: impl Into<Vec<u8>>) -> Self {
+        PemSource::Bytes(data.into())
+    }
+    /// 立即读完`reader`
+    pub fn reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Ok(PemSource::Bytes(data))
+    }
+    pub fn env(name: &str) -> Self {
+        PemSource::Env(name.to_string())
+    }
+    pub fn credential(name: &str) -> Self {
+        PemSource::Credential(name.to_string())
+    }
+
+    /// 读取PEM内容，环境变量中的base64会先解码
+    pub fn read(&self) -> Result<Vec<u8>, KeyError> {
+        let io_err = |error| KeyError::Io {
+            source: self.to_string(),
+            error,
+        };
+        match self {
+            PemSource::File(path) => std::fs::read(path).map_err(io_err),
+            PemSource::Bytes(data) => Ok(data.clone()),
+            PemSource::Env(name) => {
+                let value = std::env::var(name)
+                    .map_err(|e| io_err(io::Error::new(io::ErrorKind::NotFound, e.to_string())))?;
+                decode_env_value(&value).map_err(io_err)
+            }
+            PemSource::Credential(name) => {
+                let dir = std::env::var_os("CREDENTIALS_DIRECTORY").ok_or_else(|| {
+                    io_err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        "$CREDENTIALS_DIRECTORY is not set",
+                    ))
+                })?;
+                std::fs::read(PathBuf::from(dir).join(name)).map_err(io_err)
+            }
+        }
+    }
+
+    /// 其中的全部证书，一个都没有时报错
+    pub fn certs(&self) -> Result<Vec<Certificate>> {
+        let data = self.read()?;
+        let certs = rustls_pemfile::certs(&mut io::BufReader::new(data.as_slice()))?;
+        if certs.is_empty() {
+            bail!("no certificate found in {}", self);
+        }
+        Ok(certs.into_iter().map(Certificate).collect())
+    }
+
+    pub fn root_certs(&self) -> Result<RootCertStore> {
+        let mut roots = RootCertStore::empty();
+        for cert in self.certs()? {
+            roots.add(&cert)?;
+        }
+        Ok(roots)
+    }
+
+    /// 第一个私钥，支持PKCS#1（RSA）、PKCS#8与SEC1（EC）
+    pub fn private_key(&self) -> Result<PrivateKey, KeyError> {
+        parse_private_key(&self.read()?, self.to_string())
+    }
+
+    /// 同`private_key`，另支持加密的PKCS#8，仅在私钥加密时读取口令
+    pub fn private_key_with_passphrase(
+        &self,
+        passphrase: &Passphrase,
+    ) -> Result<PrivateKey, KeyError> {
+        let data = self.read()?;
+        match parse_private_key(&data, self.to_string()) {
+            Err(KeyError::Encrypted { .. }) => {
+                decrypt_private_key(&data, self.to_string(), passphrase)
+            }
+            rs => rs,
+        }
+    }
+}
+
+fn decode_env_value(value: &str) -> io::Result<Vec<u8>> {
+    if value.trim_start().starts_with("-----BEGIN") {
+        return Ok(value.as_bytes().to_vec());
+    }
+    let compact: String = value.split_whitespace().collect();
+    Base64::decode_vec(&compact).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("neither PEM nor base64: {}", e),
+        )
+    })
+}
+
+impl From<PathBuf> for PemSource {
+    fn from(path: PathBuf) -> Self {
+        PemSource::File(path)
+    }
+}
+
+impl From<&std::path::Path> for PemSource {
+    fn from(path: &std::path::Path) -> Self {
+        PemSource::File(path.to_path_buf())
+    }
+}
+
+/// 用于错误信息，不含PEM内容
+impl Display for PemSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PemSource::File(path) => write!(f, "{}", path.display()),
+            PemSource::Bytes(data) => write!(f, "<{} bytes in memory>", data.len()),
+            PemSource::Env(name) => write!(f, "env {}", name),
+            PemSource::Credential(name) => write!(f, "credential {}", name),
+        }
+    }
+}
+
+impl Debug for PemSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PemSource::File(path) => f.debug_tuple("File").field(path).finish(),
+            PemSource::Bytes(data) => write!(f, "Bytes({} bytes)", data.len()),
+            PemSource::Env(name) => f.debug_tuple("Env").field(name).finish(),
+            PemSource::Credential(name) => f.debug_tuple("Credential").field(name).finish(),
+        }
+    }
+}
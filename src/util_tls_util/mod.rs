@@ -28,12 +28,7 @@ pub fn gen_rsa_key_pem_and_file(
     pri_path: impl AsRef<Path>,
     pub_path: impl AsRef<Path>,
 ) -> Result<(PrivateKey, PublicKey)> {
-    let mut rng = rand::thread_rng();
-    let bits = 2048;
-    let private_key = RsaPrivateKey::new(&mut rng, bits).expect("failed to generate a key");
-    let public_key = RsaPublicKey::from(&private_key);
-    let pri_str = private_key.to_pkcs1_pem(LineEnding::CRLF)?;
-    let pub_str = public_key.to_public_key_pem(LineEnding::CRLF)?;
+    let (pri_str, pub_str) = gen_rsa_key_pem()?;
     std::fs::write(pub_path, pub_str.as_bytes())?;
     std::fs::write(pri_path, pri_str.as_bytes())?;
     Ok((
@@ -42,6 +37,45 @@ pub fn gen_rsa_key_pem_and_file(
     ))
 }
 
+/// 生成RSA私钥，私钥以加密的PKCS#8格式写入文件，可由`tls::load_private_key_with_passphrase`读取
+pub fn gen_rsa_key_pem_and_file_encrypted(
+    pri_path: impl AsRef<Path>,
+    pub_path: impl AsRef<Path>,
+    passphrase: &str,
+) -> Result<(PrivateKey, PublicKey)> {
+    let (pri_str, pub_str) = gen_rsa_key_pem()?;
+    let private_key = PrivateKey::from_pem_str(pri_str.as_str())?;
+    std::fs::write(pub_path, pub_str.as_bytes())?;
+    write_encrypted_key_pem(&private_key, passphrase, pri_path)?;
+    Ok((private_key, PublicKey::from_pem_str(pub_str.as_str())?))
+}
+
+/// 生成2048位RSA私钥，返回PKCS#1私钥与公钥的PEM
+fn gen_rsa_key_pem() -> Result<(String, String)> {
+    let mut rng = rand::thread_rng();
+    let bits = 2048;
+    let private_key = RsaPrivateKey::new(&mut rng, bits)
+        .map_err(|e| anyhow!("failed to generate a key: {}", e))?;
+    let public_key = RsaPublicKey::from(&private_key);
+    let pri_str = private_key.to_pkcs1_pem(LineEnding::CRLF)?;
+    let pub_str = public_key.to_public_key_pem(LineEnding::CRLF)?;
+    Ok((pri_str.to_string(), pub_str))
+}
+
+/// 将私钥以加密的PKCS#8格式（scrypt + AES-256-CBC）写入文件，返回写入的PEM
+pub fn write_encrypted_key_pem(
+    key: &PrivateKey,
+    passphrase: &str,
+    path: impl AsRef<Path>,
+) -> Result<String> {
+    let der = key.to_pkcs8()?;
+    let info = pkcs8::PrivateKeyInfo::try_from(der.as_slice())?;
+    let encrypted = info.encrypt(rand::thread_rng(), passphrase)?;
+    let pem = encrypted.to_pem("ENCRYPTED PRIVATE KEY", pkcs8::LineEnding::CRLF)?;
+    std::fs::write(path, pem.as_bytes())?;
+    Ok(pem.to_string())
+}
+
 // pub fn gen_rsa_pkcs8_key_pem_and_file(
 //     pri_path: impl AsRef<Path>,
 //     pub_path: impl AsRef<Path>,
@@ -122,7 +156,7 @@ pub fn gen_cert_by_ca(
         .validity(from_data, to_date)
         .subject_from_csr(csr)
         .inherit_extensions_from_csr_attributes(true)
-        .issuer_cert(&ca_cert, &ca_key)
+        .issuer_cert(ca_cert, ca_key)
         .signature_hash_type(SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256))
         .key_id_gen_method(KeyIdGenMethod::SPKFullDER(HashAlgorithm::SHA2_256))
         .build()?;
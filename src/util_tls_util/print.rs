@@ -307,7 +307,7 @@ fn handle_certificate(file_name: &str, data: &[u8]) -> io::Result<()> {
         Err(e) => {
             let s = format!("Error while parsing {}: {}", file_name, e);
             if PARSE_ERRORS_FATAL {
-                Err(io::Error::new(io::ErrorKind::Other, s))
+                Err(io::Error::other(s))
             } else {
                 eprintln!("{}", s);
                 Ok(())
@@ -320,14 +320,14 @@ pub fn print_cert(file_name: &str) -> Result<()> {
     let data = std::fs::read(file_name).expect("Unable to read file");
     if matches!((data[0], data[1]), (0x30, 0x81..=0x83)) {
         // probably DER
-        handle_certificate(&file_name, &data)?;
+        handle_certificate(file_name, &data)?;
     } else {
         // try as PEM
         for (n, pem) in Pem::iter_from_buffer(&data).enumerate() {
             let pem = pem.expect("Could not decode the PEM file");
             let data = &pem.contents;
             println!("Certificate [{}]", n);
-            handle_certificate(&file_name, data)?;
+            handle_certificate(file_name, data)?;
         }
     }
     Ok(())
@@ -0,0 +1,44 @@
+use crate::util_txrx::ChannelError;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// 批量接收的结果；`closed`为true表示通道已关闭，这是最后一批
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Batch<T> {
+    pub items: Vec<T>,
+    pub closed: bool,
+}
+
+/// 批量接收最多`max`条，收满`max`条或自调用起经过`wait`时返回（以先到者为准），
+/// 期间没有消息时返回空批次。`max`为0时立即返回空批次。
+/// 通道关闭且没有任何消息时返回[`ChannelError::Closed`]
+pub trait BatchRecvExt<T> {
+    fn recv_batch(&self, max: usize, wait: Duration) -> Result<Batch<T>, ChannelError>;
+}
+
+impl<T> BatchRecvExt<T> for Receiver<T> {
+    fn recv_batch(&self, max: usize, wait: Duration) -> Result<Batch<T>, ChannelError> {
+        let deadline = Instant::now() + wait;
+        let mut items = Vec::new();
+        while items.len() < max {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.recv_timeout(timeout) {
+                Ok(val) => items.push(val),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) if items.is_empty() => {
+                    return Err(ChannelError::Closed)
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Ok(Batch {
+                        items,
+                        closed: true,
+                    })
+                }
+            }
+        }
+        Ok(Batch {
+            items,
+            closed: false,
+        })
+    }
+}
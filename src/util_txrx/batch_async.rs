@@ -0,0 +1,49 @@
+use crate::util_txrx::{Batch, ChannelError};
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::mpsc::{Receiver, UnboundedReceiver};
+use tokio::time::Instant;
+
+/// 与[`crate::txrx::BatchRecvExt`]语义相同的异步版本
+pub trait AsyncBatchRecvExt<T>: Send {
+    fn recv_batch_async(
+        &mut self,
+        max: usize,
+        wait: Duration,
+    ) -> impl Future<Output = Result<Batch<T>, ChannelError>> + Send;
+}
+
+macro_rules! impl_async_batch {
+    ($rx:ident) => {
+        impl<T: Send> AsyncBatchRecvExt<T> for $rx<T> {
+            async fn recv_batch_async(
+                &mut self,
+                max: usize,
+                wait: Duration,
+            ) -> Result<Batch<T>, ChannelError> {
+                let deadline = Instant::now() + wait;
+                let mut items = Vec::new();
+                while items.len() < max {
+                    match tokio::time::timeout_at(deadline, self.recv()).await {
+                        Ok(Some(val)) => items.push(val),
+                        Ok(None) if items.is_empty() => return Err(ChannelError::Closed),
+                        Ok(None) => {
+                            return Ok(Batch {
+                                items,
+                                closed: true,
+                            })
+                        }
+                        Err(_) => break,
+                    }
+                }
+                Ok(Batch {
+                    items,
+                    closed: false,
+                })
+            }
+        }
+    };
+}
+
+impl_async_batch!(Receiver);
+impl_async_batch!(UnboundedReceiver);
@@ -0,0 +1,327 @@
+use crate::util_txrx::ext::log_err;
+use crate::util_txrx::{ChannelError, RecvExt, SendExt};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// 通道已满时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// 等待有空位
+    Block,
+    /// 丢弃正在发送的消息
+    DropNewest,
+    /// 丢弃队列中最旧的消息，再放入新消息
+    DropOldest,
+    /// 最多等待指定时长，仍无空位则返回[`ChannelError::Timeout`]
+    ErrorAfter(Duration),
+}
+
+/// 按`policy`处理溢出的有界通道，同步（`send`/`recv`）与异步（`send_async`/`recv_async`）均可使用
+pub fn bounded<T>(cap: usize, policy: OverflowPolicy) -> (BoundedSender<T>, BoundedReceiver<T>) {
+    assert!(cap > 0, "capacity must be greater than 0");
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: VecDeque::with_capacity(cap),
+            senders: 1,
+            receiver_alive: true,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        #[cfg(feature = "txrx-async")]
+        not_empty_async: tokio::sync::Notify::new(),
+        #[cfg(feature = "txrx-async")]
+        not_full_async: tokio::sync::Notify::new(),
+        dropped: AtomicU64::new(0),
+        cap,
+        policy,
+    });
+    (
+        BoundedSender {
+            shared: shared.clone(),
+        },
+        BoundedReceiver { shared },
+    )
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    receiver_alive: bool,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    #[cfg(feature = "txrx-async")]
+    not_empty_async: tokio::sync::Notify,
+    #[cfg(feature = "txrx-async")]
+    not_full_async: tokio::sync::Notify,
+    dropped: AtomicU64,
+    cap: usize,
+    policy: OverflowPolicy,
+}
+
+/// `try_push`的结果：`Full`表示需要等待；
+/// 被丢弃或未送出的消息须在释放锁之后再drop，以免其`Drop`再次加锁
+enum Push<T> {
+    Done,
+    Full(T),
+    Dropped(T),
+    Closed(T),
+}
+
+impl<T> Push<T> {
+    fn finish(self) -> Result<(), ChannelError> {
+        match self {
+            Push::Done | Push::Dropped(_) => Ok(()),
+            Push::Closed(_) => Err(ChannelError::Closed),
+            Push::Full(_) => unreachable!(),
+        }
+    }
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap()
+    }
+    fn wake_receiver(&self) {
+        self.not_empty.notify_one();
+        #[cfg(feature = "txrx-async")]
+        self.not_empty_async.notify_waiters();
+    }
+    fn wake_senders(&self) {
+        self.not_full.notify_all();
+        #[cfg(feature = "txrx-async")]
+        self.not_full_async.notify_waiters();
+    }
+    fn try_push(&self, state: &mut State<T>, val: T) -> Push<T> {
+        if !state.receiver_alive {
+            return Push::Closed(val);
+        }
+        if state.queue.len() < self.cap {
+            state.queue.push_back(val);
+            self.wake_receiver();
+            return Push::Done;
+        }
+        match self.policy {
+            OverflowPolicy::DropNewest => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                Push::Dropped(val)
+            }
+            OverflowPolicy::DropOldest => {
+                let oldest = state.queue.pop_front();
+                state.queue.push_back(val);
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                self.wake_receiver();
+                match oldest {
+                    Some(oldest) => Push::Dropped(oldest),
+                    None => Push::Done,
+                }
+            }
+            OverflowPolicy::Block | OverflowPolicy::ErrorAfter(_) => Push::Full(val),
+        }
+    }
+    fn try_pop(&self, state: &mut State<T>) -> Result<Option<T>, ChannelError> {
+        match state.queue.pop_front() {
+            Some(val) => {
+                self.wake_senders();
+                Ok(Some(val))
+            }
+            None if state.senders == 0 => Err(ChannelError::Closed),
+            None => Ok(None),
+        }
+    }
+}
+
+pub struct BoundedSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct BoundedReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for BoundedSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for BoundedSender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            self.shared.wake_receiver();
+        }
+    }
+}
+
+impl<T> Drop for BoundedReceiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.receiver_alive = false;
+        let queue = std::mem::take(&mut state.queue);
+        self.shared.wake_senders();
+        drop(state);
+        drop(queue);
+    }
+}
+
+impl<T> BoundedSender<T> {
+    pub fn policy(&self) -> OverflowPolicy {
+        self.shared.policy
+    }
+    /// 因溢出被丢弃的消息数
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// 阻塞当前线程，勿在异步任务中调用
+    pub fn send(&self, val: T) -> Result<(), ChannelError> {
+        let shared = &self.shared;
+        let mut state = shared.lock();
+        let val = match shared.try_push(&mut state, val) {
+            Push::Full(val) => val,
+            rs => {
+                drop(state);
+                return rs.finish();
+            }
+        };
+        let full = |x: &mut State<T>| x.receiver_alive && x.queue.len() >= shared.cap;
+        state = match shared.policy {
+            OverflowPolicy::ErrorAfter(timeout) => {
+                let (state, rs) = shared
+                    .not_full
+                    .wait_timeout_while(state, timeout, full)
+                    .unwrap();
+                if rs.timed_out() {
+                    drop(state);
+                    return Err(ChannelError::Timeout(timeout));
+                }
+                state
+            }
+            _ => shared.not_full.wait_while(state, full).unwrap(),
+        };
+        let rs = shared.try_push(&mut state, val);
+        drop(state);
+        rs.finish()
+    }
+    #[cfg(feature = "txrx-async")]
+    pub async fn send_async(&self, val: T) -> Result<(), ChannelError> {
+        match self.shared.policy {
+            OverflowPolicy::ErrorAfter(timeout) => {
+                match tokio::time::timeout(timeout, self.send_wait(val)).await {
+                    Ok(rs) => rs,
+                    Err(_) => Err(ChannelError::Timeout(timeout)),
+                }
+            }
+            _ => self.send_wait(val).await,
+        }
+    }
+    #[cfg(feature = "txrx-async")]
+    async fn send_wait(&self, mut val: T) -> Result<(), ChannelError> {
+        loop {
+            let notified = self.shared.not_full_async.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            let rs = self.shared.try_push(&mut self.shared.lock(), val);
+            match rs {
+                Push::Full(x) => val = x,
+                rs => return rs.finish(),
+            }
+            notified.await;
+        }
+    }
+}
+
+impl<T> BoundedReceiver<T> {
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// 阻塞当前线程，勿在异步任务中调用
+    pub fn recv(&self) -> Result<T, ChannelError> {
+        let shared = &self.shared;
+        let state = shared.lock();
+        let mut state = shared
+            .not_empty
+            .wait_while(state, |x| x.queue.is_empty() && x.senders > 0)
+            .unwrap();
+        shared.try_pop(&mut state)?.ok_or(ChannelError::Closed)
+    }
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, ChannelError> {
+        let shared = &self.shared;
+        let state = shared.lock();
+        let (mut state, _) = shared
+            .not_empty
+            .wait_timeout_while(state, timeout, |x| x.queue.is_empty() && x.senders > 0)
+            .unwrap();
+        shared
+            .try_pop(&mut state)?
+            .ok_or(ChannelError::Timeout(timeout))
+    }
+    pub fn try_recv(&self) -> Result<Option<T>, ChannelError> {
+        self.shared.try_pop(&mut self.shared.lock())
+    }
+    #[cfg(feature = "txrx-async")]
+    pub async fn recv_async(&self) -> Result<T, ChannelError> {
+        loop {
+            let notified = self.shared.not_empty_async.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if let Some(val) = self.shared.try_pop(&mut self.shared.lock())? {
+                return Ok(val);
+            }
+            notified.await;
+        }
+    }
+}
+
+impl<T> SendExt<T> for BoundedSender<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val).map_err(log_err)
+    }
+}
+
+impl<T> RecvExt<T> for BoundedReceiver<T> {
+    fn recv_or_log(&self) -> Result<T, ChannelError> {
+        self.recv().map_err(log_err)
+    }
+    fn recv_timeout_or_log(&self, timeout: Duration) -> Result<T, ChannelError> {
+        self.recv_timeout(timeout).map_err(log_err)
+    }
+    fn try_recv_or_log(&self) -> Result<Option<T>, ChannelError> {
+        self.try_recv().map_err(log_err)
+    }
+}
+
+#[cfg(feature = "txrx-async")]
+impl<T: Send> crate::util_txrx::AsyncSendExt<T> for BoundedSender<T> {
+    async fn send_or_log_async(&self, val: T) -> Result<(), ChannelError> {
+        self.send_async(val).await.map_err(log_err)
+    }
+}
+
+#[cfg(feature = "txrx-async")]
+impl<T: Send> crate::util_txrx::AsyncRecvExt<T> for BoundedReceiver<T> {
+    async fn recv_or_log_async(&mut self) -> Result<T, ChannelError> {
+        self.recv_async().await.map_err(log_err)
+    }
+}
@@ -0,0 +1,127 @@
+use crate::util_txrx::ext::log_err;
+use crate::util_txrx::{ChannelError, RecvExt, SendExt};
+use std::future::Future;
+use std::pin::pin;
+use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::Thread;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// 同步线程 -> tokio任务：发送端用`tx!`，接收端用`rx_async!`
+pub fn sync_to_async<T>(cap: usize) -> (BlockingSender<T>, mpsc::Receiver<T>) {
+    let (tx, rx) = mpsc::channel(cap);
+    (BlockingSender { inner: tx }, rx)
+}
+
+/// tokio任务 -> 同步线程：发送端用`tx_async!`，接收端用`rx!`
+pub fn async_to_sync<T>(cap: usize) -> (mpsc::Sender<T>, BlockingReceiver<T>) {
+    let (tx, rx) = mpsc::channel(cap);
+    (
+        tx,
+        BlockingReceiver {
+            inner: Mutex::new(rx),
+        },
+    )
+}
+
+/// 阻塞式发送端，接口与`std::sync::mpsc::SyncSender`一致；不可在异步上下文中调用
+pub struct BlockingSender<T> {
+    inner: mpsc::Sender<T>,
+}
+
+/// 阻塞式接收端，接口与`std::sync::mpsc::Receiver`一致；不可在异步上下文中调用
+pub struct BlockingReceiver<T> {
+    inner: Mutex<mpsc::Receiver<T>>,
+}
+
+impl<T> Clone for BlockingSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> BlockingSender<T> {
+    pub fn send(&self, val: T) -> Result<(), SendError<T>> {
+        self.inner.blocking_send(val).map_err(|e| SendError(e.0))
+    }
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+impl<T> BlockingReceiver<T> {
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.inner.lock().unwrap().blocking_recv().ok_or(RecvError)
+    }
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let mut rx = self.inner.lock().unwrap();
+        match block_on_timeout(rx.recv(), timeout) {
+            Some(Some(val)) => Ok(val),
+            Some(None) => Err(RecvTimeoutError::Disconnected),
+            None => Err(RecvTimeoutError::Timeout),
+        }
+    }
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        use tokio::sync::mpsc::error::TryRecvError as AsyncTryRecvError;
+        match self.inner.lock().unwrap().try_recv() {
+            Ok(val) => Ok(val),
+            Err(AsyncTryRecvError::Empty) => Err(TryRecvError::Empty),
+            Err(AsyncTryRecvError::Disconnected) => Err(TryRecvError::Disconnected),
+        }
+    }
+}
+
+impl<T> SendExt<T> for BlockingSender<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val).map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T> RecvExt<T> for BlockingReceiver<T> {
+    fn recv_or_log(&self) -> Result<T, ChannelError> {
+        self.recv().map_err(|_| log_err(ChannelError::Closed))
+    }
+    fn recv_timeout_or_log(&self, timeout: Duration) -> Result<T, ChannelError> {
+        self.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => log_err(ChannelError::Timeout(timeout)),
+            RecvTimeoutError::Disconnected => log_err(ChannelError::Closed),
+        })
+    }
+    fn try_recv_or_log(&self) -> Result<Option<T>, ChannelError> {
+        match self.try_recv() {
+            Ok(val) => Ok(Some(val)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(log_err(ChannelError::Closed)),
+        }
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// 在当前线程上驱动不依赖tokio计时器的future，超时返回None
+fn block_on_timeout<F: Future>(fut: F, timeout: Duration) -> Option<F::Output> {
+    let deadline = Instant::now() + timeout;
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = pin!(fut);
+    loop {
+        if let Poll::Ready(val) = fut.as_mut().poll(&mut cx) {
+            return Some(val);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return None;
+        }
+        std::thread::park_timeout(deadline - now);
+    }
+}
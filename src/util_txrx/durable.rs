@@ -0,0 +1,406 @@
+use anyhow::{anyhow, bail, Context, Result};
+use log::{debug, info, warn};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Notify;
+
+/// 单个分段文件的默认大小上限
+pub const DEFAULT_SEGMENT_SIZE: u64 = 8 * 1024 * 1024;
+/// seq(u64) + len(u32)
+const HEADER_LEN: usize = 12;
+/// 确认记录：seq(u64)
+const ACK_LEN: u64 = 8;
+
+type Record = (u64, Vec<u8>);
+
+/// 可写入持久化队列的消息
+pub trait DurableItem: Sized {
+    fn encode(&self) -> Vec<u8>;
+    fn decode(bytes: &[u8]) -> Result<Self>;
+}
+
+impl DurableItem for Vec<u8> {
+    fn encode(&self) -> Vec<u8> {
+        self.clone()
+    }
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl DurableItem for String {
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+/// 从队列取出的消息，处理完成后需调用`DurableReceiver::ack`，否则重启后会重新投递
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry<T> {
+    pub seq: u64,
+    pub item: T,
+}
+
+/// 在目录/var/local/lib/{app}/{name}下创建持久化队列
+pub fn durable_queue_default<T: DurableItem>(
+    app: &str,
+    name: &str,
+) -> Result<(DurableSender<T>, DurableReceiver<T>)> {
+    let dir = PathBuf::from_str("/var/local/lib")?.join(app).join(name);
+    durable_queue(dir)
+}
+
+/// 在`dir`下创建（或恢复）持久化队列，分段大小为[`DEFAULT_SEGMENT_SIZE`]
+pub fn durable_queue<T: DurableItem>(
+    dir: impl AsRef<Path>,
+) -> Result<(DurableSender<T>, DurableReceiver<T>)> {
+    durable_queue_with_segment_size(dir, DEFAULT_SEGMENT_SIZE)
+}
+
+/// 在`dir`下创建（或恢复）持久化队列：消息追加写入分段文件`{首个序号}.log`，
+/// 确认记录写入同名的`.ack`；分段超过`segment_size`后写入新分段，
+/// 消息全部确认的旧分段被删除。启动时重放所有未确认的消息
+pub fn durable_queue_with_segment_size<T: DurableItem>(
+    dir: impl AsRef<Path>,
+    segment_size: u64,
+) -> Result<(DurableSender<T>, DurableReceiver<T>)> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir).with_context(|| format!("create dir {:?}", dir))?;
+    let mut segments = BTreeMap::new();
+    let mut next_seq = 0;
+    let mut pending = VecDeque::new();
+    let mut unacked = BTreeSet::new();
+    for first_seq in list_segments(dir)? {
+        let mut segment = Segment::open(dir, first_seq)?;
+        let acked = read_acks(&segment)?;
+        next_seq = next_seq.max(first_seq);
+        for (seq, bytes) in read_records(&mut segment)? {
+            next_seq = next_seq.max(seq + 1);
+            if acked.contains(&seq) {
+                continue;
+            }
+            let item = T::decode(&bytes).with_context(|| format!("decode record {}", seq))?;
+            unacked.insert(seq);
+            pending.push_back(Entry { seq, item });
+        }
+        segments.insert(first_seq, segment);
+    }
+    if !pending.is_empty() {
+        info!(
+            "replay {} unacknowledged entries from {:?}",
+            pending.len(),
+            dir
+        );
+    }
+    if segments.is_empty() {
+        segments.insert(next_seq, Segment::open(dir, next_seq)?);
+    }
+    let mut state = State {
+        dir: dir.to_path_buf(),
+        segment_size,
+        segments,
+        next_seq,
+        pending,
+        unacked,
+        senders: 1,
+        receiver_alive: true,
+    };
+    state.compact()?;
+    let shared = Arc::new(Shared {
+        state: Mutex::new(state),
+        notify: Notify::new(),
+    });
+    Ok((
+        DurableSender {
+            shared: shared.clone(),
+        },
+        DurableReceiver { shared },
+    ))
+}
+
+/// 目录中已有分段的首个序号，升序
+fn list_segments(dir: &Path) -> Result<Vec<u64>> {
+    let mut segments = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("read dir {:?}", dir))? {
+        let path = entry?.path();
+        if path.extension().and_then(|x| x.to_str()) != Some("log") {
+            continue;
+        }
+        if let Some(first_seq) = path
+            .file_stem()
+            .and_then(|x| x.to_str())
+            .and_then(|x| x.parse().ok())
+        {
+            segments.push(first_seq);
+        }
+    }
+    segments.sort_unstable();
+    Ok(segments)
+}
+
+/// 读取确认记录，截掉尾部不完整的记录
+fn read_acks(segment: &Segment) -> Result<HashSet<u64>> {
+    let bytes =
+        std::fs::read(&segment.ack_path).with_context(|| format!("read {:?}", segment.ack_path))?;
+    let valid_len = bytes.len() as u64 / ACK_LEN * ACK_LEN;
+    if bytes.len() as u64 > valid_len {
+        warn!(
+            "truncate incomplete ack at the tail of {:?}",
+            segment.ack_path
+        );
+        segment.ack.set_len(valid_len)?;
+    }
+    Ok(bytes
+        .chunks_exact(ACK_LEN as usize)
+        .map(|x| u64::from_le_bytes(x.try_into().unwrap()))
+        .collect())
+}
+
+/// 读取完整的记录，截掉尾部不完整的记录
+fn read_records(segment: &mut Segment) -> Result<Vec<Record>> {
+    let bytes =
+        std::fs::read(&segment.log_path).with_context(|| format!("read {:?}", segment.log_path))?;
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while bytes.len() - offset >= HEADER_LEN {
+        let seq = u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        let len = u32::from_le_bytes(bytes[offset + 8..offset + 12].try_into().unwrap()) as usize;
+        let start = offset + HEADER_LEN;
+        if bytes.len() - start < len {
+            break;
+        }
+        records.push((seq, bytes[start..start + len].to_vec()));
+        offset = start + len;
+    }
+    if bytes.len() > offset {
+        warn!(
+            "truncate incomplete record at the tail of {:?}",
+            segment.log_path
+        );
+        segment.log.set_len(offset as u64)?;
+        segment.len = offset as u64;
+    }
+    Ok(records)
+}
+
+/// 一个分段：`{首个序号}.log`与`{首个序号}.ack`
+struct Segment {
+    log_path: PathBuf,
+    ack_path: PathBuf,
+    log: File,
+    ack: File,
+    len: u64,
+}
+
+impl Segment {
+    fn open(dir: &Path, first_seq: u64) -> Result<Self> {
+        let log_path = dir.join(format!("{:020}.log", first_seq));
+        let ack_path = dir.join(format!("{:020}.ack", first_seq));
+        let open = |path: &Path| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("open {:?}", path))
+        };
+        let log = open(&log_path)?;
+        let ack = open(&ack_path)?;
+        let len = log.metadata()?.len();
+        Ok(Self {
+            log_path,
+            ack_path,
+            log,
+            ack,
+            len,
+        })
+    }
+    fn remove(self) -> Result<()> {
+        std::fs::remove_file(&self.log_path)?;
+        std::fs::remove_file(&self.ack_path)?;
+        Ok(())
+    }
+}
+
+struct State<T> {
+    dir: PathBuf,
+    segment_size: u64,
+    /// 首个序号 -> 分段，最后一个为当前写入的分段
+    segments: BTreeMap<u64, Segment>,
+    next_seq: u64,
+    /// 尚未取出的消息
+    pending: VecDeque<Entry<T>>,
+    /// 已写入但未确认的序号（含已取出的）
+    unacked: BTreeSet<u64>,
+    senders: usize,
+    receiver_alive: bool,
+}
+
+impl<T> State<T> {
+    fn active(&mut self) -> &mut Segment {
+        self.segments.values_mut().next_back().unwrap()
+    }
+    /// `seq`所在的分段
+    fn segment_of(&mut self, seq: u64) -> &mut Segment {
+        let (_, segment) = self.segments.range_mut(..=seq).next_back().unwrap();
+        segment
+    }
+    /// 写入当前分段，超过大小上限时从`seq + 1`开始新分段
+    fn append(&mut self, seq: u64, record: &[u8]) -> Result<()> {
+        let segment_size = self.segment_size;
+        let active = self.active();
+        if let Err(e) = active.log.write_all(record) {
+            // 截掉写了一半的记录，否则之后的记录都会从错误的位置读取
+            active
+                .log
+                .set_len(active.len)
+                .with_context(|| format!("truncate {:?}", active.log_path))?;
+            return Err(e).with_context(|| format!("write {:?}", active.log_path));
+        }
+        active.len += record.len() as u64;
+        if active.len >= segment_size {
+            active.log.sync_data()?;
+            let segment = Segment::open(&self.dir, seq + 1)?;
+            debug!("durable queue rotated to {:?}", segment.log_path);
+            self.segments.insert(seq + 1, segment);
+        }
+        Ok(())
+    }
+    /// 删除消息全部确认的旧分段；所有消息都已确认时清空当前分段
+    fn compact(&mut self) -> Result<()> {
+        let firsts: Vec<u64> = self.segments.keys().copied().collect();
+        for (index, first) in firsts.iter().enumerate() {
+            let Some(next) = firsts.get(index + 1) else {
+                break;
+            };
+            if self.unacked.range(first..next).next().is_none() {
+                let segment = self.segments.remove(first).unwrap();
+                debug!("durable queue removed {:?}", segment.log_path);
+                segment.remove()?;
+            }
+        }
+        if self.unacked.is_empty() {
+            let active = self.active();
+            if active.len > 0 {
+                active.log.set_len(0)?;
+                active.ack.set_len(0)?;
+                active.len = 0;
+                debug!("durable queue compacted");
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    notify: Notify,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap()
+    }
+}
+
+pub struct DurableSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct DurableReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for DurableSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for DurableSender<T> {
+    fn drop(&mut self) {
+        self.shared.lock().senders -= 1;
+        self.shared.notify.notify_waiters();
+    }
+}
+
+impl<T> Drop for DurableReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.lock().receiver_alive = false;
+    }
+}
+
+impl<T: DurableItem> DurableSender<T> {
+    /// 写入文件后才放入队列，可用于`tx!`
+    pub fn send(&self, item: T) -> Result<u64> {
+        let bytes = item.encode();
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| anyhow!("durable queue item too large: {} bytes", bytes.len()))?;
+        let mut state = self.shared.lock();
+        if !state.receiver_alive {
+            bail!("durable queue receiver dropped");
+        }
+        let seq = state.next_seq;
+        let mut record = Vec::with_capacity(HEADER_LEN + bytes.len());
+        record.extend_from_slice(&seq.to_le_bytes());
+        record.extend_from_slice(&len.to_le_bytes());
+        record.extend_from_slice(&bytes);
+        state.append(seq, &record)?;
+        state.next_seq += 1;
+        state.unacked.insert(seq);
+        state.pending.push_back(Entry { seq, item });
+        drop(state);
+        self.shared.notify.notify_waiters();
+        Ok(seq)
+    }
+    /// 将已写入的数据同步到磁盘
+    pub fn sync(&self) -> Result<()> {
+        Ok(self.shared.lock().active().log.sync_data()?)
+    }
+}
+
+impl<T> DurableReceiver<T> {
+    /// 所有发送端都已丢弃且队列为空时返回`None`，可用于`rx_async!`
+    pub async fn recv(&mut self) -> Option<Entry<T>> {
+        loop {
+            let notified = self.shared.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let mut state = self.shared.lock();
+                if let Some(entry) = state.pending.pop_front() {
+                    return Some(entry);
+                }
+                if state.senders == 0 {
+                    return None;
+                }
+            }
+            notified.await;
+        }
+    }
+    pub fn try_recv(&mut self) -> Option<Entry<T>> {
+        self.shared.lock().pending.pop_front()
+    }
+    /// 确认消息已处理完成
+    pub fn ack(&self, seq: u64) -> Result<()> {
+        let mut state = self.shared.lock();
+        if !state.unacked.remove(&seq) {
+            return Ok(());
+        }
+        state.segment_of(seq).ack.write_all(&seq.to_le_bytes())?;
+        state.compact()
+    }
+    /// 未确认的消息数（含尚未取出的）
+    pub fn unacked(&self) -> usize {
+        self.shared.lock().unacked.len()
+    }
+}
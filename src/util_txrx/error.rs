@@ -0,0 +1,37 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// 通道操作失败的原因，可通过`anyhow::Error::downcast_ref`区分超时与关闭
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelError {
+    /// 对端已关闭
+    Closed,
+    /// 在给定时间内未完成
+    Timeout(Duration),
+    /// 接收端落后，丢失了若干条消息（broadcast）
+    Lagged(u64),
+    /// 请求已送达，但对端未应答就丢弃了请求
+    ReplyDropped,
+}
+
+impl ChannelError {
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, ChannelError::Timeout(_))
+    }
+    pub fn is_closed(&self) -> bool {
+        matches!(self, ChannelError::Closed)
+    }
+}
+
+impl Display for ChannelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelError::Closed => write!(f, "channel closed"),
+            ChannelError::Timeout(timeout) => write!(f, "channel timeout after {:?}", timeout),
+            ChannelError::Lagged(skipped) => write!(f, "channel lagged, skipped {}", skipped),
+            ChannelError::ReplyDropped => write!(f, "request dropped without reply"),
+        }
+    }
+}
+
+impl std::error::Error for ChannelError {}
@@ -0,0 +1,117 @@
+use crate::util_txrx::ChannelError;
+use log::error;
+use std::time::Duration;
+
+/// 同步发送，失败时记录日志并返回[`ChannelError`]
+pub trait SendExt<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError>;
+}
+
+/// 一次性发送（如`oneshot::Sender`），会消耗发送端
+pub trait SendOnceExt<T> {
+    fn send_or_log(self, val: T) -> Result<(), ChannelError>;
+}
+
+/// 同步接收，失败时记录日志并返回[`ChannelError`]
+pub trait RecvExt<T> {
+    fn recv_or_log(&self) -> Result<T, ChannelError>;
+    fn recv_timeout_or_log(&self, timeout: Duration) -> Result<T, ChannelError>;
+    /// 通道为空时返回`Ok(None)`
+    fn try_recv_or_log(&self) -> Result<Option<T>, ChannelError>;
+}
+
+pub(crate) fn log_err(err: ChannelError) -> ChannelError {
+    error!("{}", err);
+    err
+}
+
+impl<T> SendExt<T> for std::sync::mpsc::Sender<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val).map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T> SendExt<T> for std::sync::mpsc::SyncSender<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val).map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T> RecvExt<T> for std::sync::mpsc::Receiver<T> {
+    fn recv_or_log(&self) -> Result<T, ChannelError> {
+        self.recv().map_err(|_| log_err(ChannelError::Closed))
+    }
+    fn recv_timeout_or_log(&self, timeout: Duration) -> Result<T, ChannelError> {
+        use std::sync::mpsc::RecvTimeoutError;
+        self.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => log_err(ChannelError::Timeout(timeout)),
+            RecvTimeoutError::Disconnected => log_err(ChannelError::Closed),
+        })
+    }
+    fn try_recv_or_log(&self) -> Result<Option<T>, ChannelError> {
+        use std::sync::mpsc::TryRecvError;
+        match self.try_recv() {
+            Ok(val) => Ok(Some(val)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(log_err(ChannelError::Closed)),
+        }
+    }
+}
+
+#[cfg(feature = "crossbeam-channel")]
+impl<T> SendExt<T> for crossbeam_channel::Sender<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val).map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+#[cfg(feature = "crossbeam-channel")]
+impl<T> RecvExt<T> for crossbeam_channel::Receiver<T> {
+    fn recv_or_log(&self) -> Result<T, ChannelError> {
+        self.recv().map_err(|_| log_err(ChannelError::Closed))
+    }
+    fn recv_timeout_or_log(&self, timeout: Duration) -> Result<T, ChannelError> {
+        use crossbeam_channel::RecvTimeoutError;
+        self.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => log_err(ChannelError::Timeout(timeout)),
+            RecvTimeoutError::Disconnected => log_err(ChannelError::Closed),
+        })
+    }
+    fn try_recv_or_log(&self) -> Result<Option<T>, ChannelError> {
+        use crossbeam_channel::TryRecvError;
+        match self.try_recv() {
+            Ok(val) => Ok(Some(val)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(log_err(ChannelError::Closed)),
+        }
+    }
+}
+
+#[cfg(feature = "flume")]
+impl<T> SendExt<T> for flume::Sender<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val).map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+#[cfg(feature = "flume")]
+impl<T> RecvExt<T> for flume::Receiver<T> {
+    fn recv_or_log(&self) -> Result<T, ChannelError> {
+        self.recv().map_err(|_| log_err(ChannelError::Closed))
+    }
+    fn recv_timeout_or_log(&self, timeout: Duration) -> Result<T, ChannelError> {
+        use flume::RecvTimeoutError;
+        self.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => log_err(ChannelError::Timeout(timeout)),
+            RecvTimeoutError::Disconnected => log_err(ChannelError::Closed),
+        })
+    }
+    fn try_recv_or_log(&self) -> Result<Option<T>, ChannelError> {
+        use flume::TryRecvError;
+        match self.try_recv() {
+            Ok(val) => Ok(Some(val)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(log_err(ChannelError::Closed)),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::util_txrx::ext::log_err;
+use crate::util_txrx::{ChannelError, SendExt, SendOnceExt};
+use log::warn;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+
+/// 异步发送，失败时记录日志并返回[`ChannelError`]
+pub trait AsyncSendExt<T: Send>: Sync {
+    fn send_or_log_async(&self, val: T) -> impl Future<Output = Result<(), ChannelError>> + Send;
+
+    fn send_timeout_or_log_async(
+        &self,
+        val: T,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<(), ChannelError>> + Send {
+        async move {
+            match tokio::time::timeout(timeout, self.send_or_log_async(val)).await {
+                Ok(rs) => rs,
+                Err(_) => Err(log_err(ChannelError::Timeout(timeout))),
+            }
+        }
+    }
+}
+
+/// 异步接收，失败时记录日志并返回[`ChannelError`]
+pub trait AsyncRecvExt<T>: Send {
+    fn recv_or_log_async(&mut self) -> impl Future<Output = Result<T, ChannelError>> + Send;
+
+    fn recv_timeout_or_log_async(
+        &mut self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<T, ChannelError>> + Send {
+        async move {
+            match tokio::time::timeout(timeout, self.recv_or_log_async()).await {
+                Ok(rs) => rs,
+                Err(_) => Err(log_err(ChannelError::Timeout(timeout))),
+            }
+        }
+    }
+}
+
+impl<T: Send> AsyncSendExt<T> for mpsc::Sender<T> {
+    async fn send_or_log_async(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val)
+            .await
+            .map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T> SendExt<T> for mpsc::UnboundedSender<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val).map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T> SendExt<T> for broadcast::Sender<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val)
+            .map(|_| ())
+            .map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T> SendExt<T> for watch::Sender<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val).map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T> SendOnceExt<T> for oneshot::Sender<T> {
+    fn send_or_log(self, val: T) -> Result<(), ChannelError> {
+        self.send(val).map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T: Send> AsyncRecvExt<T> for mpsc::Receiver<T> {
+    async fn recv_or_log_async(&mut self) -> Result<T, ChannelError> {
+        self.recv()
+            .await
+            .ok_or_else(|| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T: Send> AsyncRecvExt<T> for mpsc::UnboundedReceiver<T> {
+    async fn recv_or_log_async(&mut self) -> Result<T, ChannelError> {
+        self.recv()
+            .await
+            .ok_or_else(|| log_err(ChannelError::Closed))
+    }
+}
+
+/// 落后时返回[`ChannelError::Lagged`]，之后可以继续接收
+impl<T: Clone + Send> AsyncRecvExt<T> for broadcast::Receiver<T> {
+    async fn recv_or_log_async(&mut self) -> Result<T, ChannelError> {
+        match self.recv().await {
+            Ok(val) => Ok(val),
+            Err(broadcast::error::RecvError::Closed) => Err(log_err(ChannelError::Closed)),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("broadcast receiver lagged, skipped {} messages", skipped);
+                Err(ChannelError::Lagged(skipped))
+            }
+        }
+    }
+}
+
+/// 等待值变化并返回新值
+impl<T: Clone + Send + Sync> AsyncRecvExt<T> for watch::Receiver<T> {
+    async fn recv_or_log_async(&mut self) -> Result<T, ChannelError> {
+        match self.changed().await {
+            Ok(_) => Ok(self.borrow_and_update().clone()),
+            Err(_) => Err(log_err(ChannelError::Closed)),
+        }
+    }
+}
+
+/// 值已取走后再次调用返回`ChannelError::Closed`
+impl<T: Send> AsyncRecvExt<T> for oneshot::Receiver<T> {
+    async fn recv_or_log_async(&mut self) -> Result<T, ChannelError> {
+        match self.try_recv() {
+            Ok(val) => Ok(val),
+            // 直接await已完成的oneshot会panic
+            Err(oneshot::error::TryRecvError::Closed) => Err(log_err(ChannelError::Closed)),
+            Err(oneshot::error::TryRecvError::Empty) => {
+                self.await.map_err(|_| log_err(ChannelError::Closed))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "flume")]
+impl<T: Send> AsyncSendExt<T> for flume::Sender<T> {
+    async fn send_or_log_async(&self, val: T) -> Result<(), ChannelError> {
+        self.send_async(val)
+            .await
+            .map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+#[cfg(feature = "flume")]
+impl<T: Send> AsyncRecvExt<T> for flume::Receiver<T> {
+    async fn recv_or_log_async(&mut self) -> Result<T, ChannelError> {
+        self.recv_async()
+            .await
+            .map_err(|_| log_err(ChannelError::Closed))
+    }
+}
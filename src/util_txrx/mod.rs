@@ -1,13 +1,79 @@
+mod batch;
+#[cfg(feature = "txrx-async")]
+mod batch_async;
+mod bounded;
+#[cfg(feature = "txrx-async")]
+mod bridge;
+#[cfg(feature = "txrx-async")]
+mod durable;
+mod error;
+mod ext;
+#[cfg(feature = "txrx-async")]
+mod ext_async;
+mod named;
+#[cfg(feature = "txrx-async")]
+mod named_async;
+#[cfg(feature = "txrx-async")]
+mod request;
+mod watchdog;
+
+pub use batch::*;
+#[cfg(feature = "txrx-async")]
+pub use batch_async::*;
+pub use bounded::*;
+#[cfg(feature = "txrx-async")]
+pub use bridge::*;
+#[cfg(feature = "txrx-async")]
+pub use durable::*;
+pub use error::*;
+pub use ext::*;
+#[cfg(feature = "txrx-async")]
+pub use ext_async::*;
+pub use named::*;
+#[cfg(feature = "txrx-async")]
+pub use named_async::*;
+#[cfg(feature = "txrx-async")]
+pub use request::*;
+pub use watchdog::*;
+
+#[cfg(feature = "txrx-async")]
+#[doc(hidden)]
+pub use tokio as __tokio;
+
+/// 为宏中的通道操作登记看门狗，名称取自[`WatchName`](crate::txrx::WatchName)；
+/// `$ch`为已求值的通道引用，`$x`只用于取表达式文本
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __watch_op {
+    ($ch:ident, $x:expr, $kind:ident) => {
+        $crate::txrx::watch_op(
+            {
+                use $crate::txrx::WatchName as _;
+                (*$ch).watch_name(stringify!($x))
+            },
+            $crate::txrx::OpKind::$kind,
+        )
+    };
+}
+
 #[macro_export]
 macro_rules! tx {
     ( $x:expr, $y:expr) => {
-        if $x.send($y).is_err() {
+        if {
+            let __ch = &$x;
+            let _watch = $crate::__watch_op!(__ch, $x, Send);
+            __ch.send($y).is_err()
+        } {
             error!("fail to send data!");
             bail!("fail to send data!")
         }
     };
     ($x:expr, $y:expr, $msg:expr) => {
-        if $x.send($y).is_err() {
+        if {
+            let __ch = &$x;
+            let _watch = $crate::__watch_op!(__ch, $x, Send);
+            __ch.send($y).is_err()
+        } {
             error!($msg);
             bail!($msg)
         }
@@ -15,14 +81,43 @@ macro_rules! tx {
 }
 #[macro_export]
 macro_rules! tx_async {
+    ( $x:expr, $y:expr, timeout = $timeout:expr) => {
+        $crate::tx_async!($x, $y, timeout = $timeout, "fail to send data!")
+    };
+    ( $x:expr, $y:expr, timeout = $timeout:expr, $msg:expr) => {{
+        let timeout = $timeout;
+        match {
+            let __ch = &$x;
+            let _watch = $crate::__watch_op!(__ch, $x, Send);
+            $crate::txrx::__tokio::time::timeout(timeout, __ch.send($y)).await
+        } {
+            Ok(Ok(_)) => {}
+            Ok(Err(_)) => {
+                error!("{}: {}", $msg, $crate::txrx::ChannelError::Closed);
+                bail!($crate::txrx::ChannelError::Closed)
+            }
+            Err(_) => {
+                error!("{}: {}", $msg, $crate::txrx::ChannelError::Timeout(timeout));
+                bail!($crate::txrx::ChannelError::Timeout(timeout))
+            }
+        }
+    }};
     ( $x:expr, $y:expr) => {
-        if $x.send($y).await.is_err() {
+        if {
+            let __ch = &$x;
+            let _watch = $crate::__watch_op!(__ch, $x, Send);
+            __ch.send($y).await.is_err()
+        } {
             error!("fail to send data!");
             bail!("fail to send data!")
         }
     };
     ($x:expr, $y:expr, $msg:expr) => {
-        if $x.send($y).await.is_err() {
+        if {
+            let __ch = &$x;
+            let _watch = $crate::__watch_op!(__ch, $x, Send);
+            __ch.send($y).await.is_err()
+        } {
             error!($msg);
             bail!($msg)
         }
@@ -30,8 +125,33 @@ macro_rules! tx_async {
 }
 #[macro_export]
 macro_rules! rx {
+    ( $x:expr, timeout = $timeout:expr) => {
+        $crate::rx!($x, timeout = $timeout, "fail to receive data!")
+    };
+    ( $x:expr, timeout = $timeout:expr, $msg:expr) => {{
+        let timeout = $timeout;
+        match {
+            let __ch = &$x;
+            let _watch = $crate::__watch_op!(__ch, $x, Recv);
+            __ch.recv_timeout(timeout)
+        } {
+            Ok(val) => val,
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                error!("{}: {}", $msg, $crate::txrx::ChannelError::Closed);
+                bail!($crate::txrx::ChannelError::Closed);
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                error!("{}: {}", $msg, $crate::txrx::ChannelError::Timeout(timeout));
+                bail!($crate::txrx::ChannelError::Timeout(timeout));
+            }
+        }
+    }};
     ( $x:expr) => {
-        match $x.recv() {
+        match {
+            let __ch = &$x;
+            let _watch = $crate::__watch_op!(__ch, $x, Recv);
+            __ch.recv()
+        } {
             Ok(val) => val,
             Err(e) => {
                 error!("{:?}", e);
@@ -40,7 +160,11 @@ macro_rules! rx {
         }
     };
     ( $x:expr, $msg:expr) => {
-        match $x.recv() {
+        match {
+            let __ch = &$x;
+            let _watch = $crate::__watch_op!(__ch, $x, Recv);
+            __ch.recv()
+        } {
             Ok(val) => val,
             Err(_) => {
                 error!($msg);
@@ -52,19 +176,99 @@ macro_rules! rx {
 
 #[macro_export]
 macro_rules! rx_async {
+    ( $x:expr, timeout = $timeout:expr) => {
+        $crate::rx_async!($x, timeout = $timeout, "fail to receive data!")
+    };
+    ( $x:expr, timeout = $timeout:expr, $msg:expr) => {{
+        use $crate::txrx::ChannelMut as _;
+        let timeout = $timeout;
+        match $x.__channel_mut() {
+            __ch => match {
+                let _watch = $crate::__watch_op!(__ch, $x, Recv);
+                $crate::txrx::__tokio::time::timeout(timeout, __ch.recv()).await
+            } {
+                Ok(Some(val)) => val,
+                Ok(None) => {
+                    error!("{}: {}", $msg, $crate::txrx::ChannelError::Closed);
+                    bail!($crate::txrx::ChannelError::Closed);
+                }
+                Err(_) => {
+                    error!("{}: {}", $msg, $crate::txrx::ChannelError::Timeout(timeout));
+                    bail!($crate::txrx::ChannelError::Timeout(timeout));
+                }
+            },
+        }
+    }};
     ( $x:expr) => {
-        match $x.recv().await {
-            Some(val) => val,
-            None => {
+        $crate::rx_async!($x, "receive none")
+    };
+    ( $x:expr, $msg:expr) => {{
+        use $crate::txrx::ChannelMut as _;
+        match $x.__channel_mut() {
+            __ch => match {
+                let _watch = $crate::__watch_op!(__ch, $x, Recv);
+                __ch.recv().await
+            } {
+                Some(val) => val,
+                None => {
+                    error!($msg);
+                    bail!($msg);
+                }
+            },
+        }
+    }};
+}
+
+/// 批量接收，返回[`Batch`](crate::txrx::Batch)；通道关闭且没有消息时记录日志并`bail!`
+#[macro_export]
+macro_rules! rx_batch {
+    ( $x:expr, $max:expr, $wait:expr) => {
+        match {
+            use $crate::txrx::BatchRecvExt as _;
+            $x.recv_batch($max, $wait)
+        } {
+            Ok(batch) => batch,
+            Err(e) => {
+                error!("{:?}", e);
+                Err(e)?
+            }
+        }
+    };
+    ( $x:expr, $max:expr, $wait:expr, $msg:expr) => {
+        match {
+            use $crate::txrx::BatchRecvExt as _;
+            $x.recv_batch($max, $wait)
+        } {
+            Ok(batch) => batch,
+            Err(_) => {
+                error!($msg);
+                bail!($msg);
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! rx_batch_async {
+    ( $x:expr, $max:expr, $wait:expr) => {
+        match {
+            use $crate::txrx::AsyncBatchRecvExt as _;
+            $x.recv_batch_async($max, $wait).await
+        } {
+            Ok(batch) => batch,
+            Err(_) => {
                 error!("receive none");
                 bail!("receive none");
             }
         }
     };
-    ( $x:expr, $msg:expr) => {
-        match $x.recv().await {
-            Some(val) => val,
-            None => {
+    ( $x:expr, $max:expr, $wait:expr, $msg:expr) => {
+        match {
+            use $crate::txrx::AsyncBatchRecvExt as _;
+            $x.recv_batch_async($max, $wait).await
+        } {
+            Ok(batch) => batch,
+            Err(_) => {
                 error!($msg);
                 bail!($msg);
             }
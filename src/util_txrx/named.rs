@@ -0,0 +1,251 @@
+use crate::util_txrx::ext::log_err;
+use crate::util_txrx::{ChannelError, RecvExt, SendExt};
+use log::warn;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{
+    sync_channel, Receiver, RecvError, RecvTimeoutError, SendError, SyncSender, TryRecvError,
+    TrySendError,
+};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+static REGISTRY: Mutex<Vec<Weak<ChannelStats>>> = Mutex::new(Vec::new());
+
+/// 具名通道的计数信息，由发送端与接收端共享
+#[derive(Debug)]
+pub struct ChannelStats {
+    name: String,
+    capacity: usize,
+    high_water: AtomicUsize,
+    sent: AtomicU64,
+    received: AtomicU64,
+    depth: AtomicUsize,
+    blocked_nanos: AtomicU64,
+    over_high_water: AtomicBool,
+}
+
+/// 某一时刻的通道状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelSnapshot {
+    pub name: String,
+    pub capacity: usize,
+    pub high_water: usize,
+    pub sent: u64,
+    pub received: u64,
+    pub depth: usize,
+    /// 发送端因通道已满而阻塞的累计时长
+    pub blocked: Duration,
+}
+
+impl ChannelStats {
+    pub(crate) fn register(name: &str, capacity: usize) -> Arc<Self> {
+        let stats = Arc::new(Self {
+            name: name.to_string(),
+            capacity,
+            // 默认容量的80%
+            high_water: AtomicUsize::new((capacity * 4 / 5).max(1)),
+            sent: AtomicU64::new(0),
+            received: AtomicU64::new(0),
+            depth: AtomicUsize::new(0),
+            blocked_nanos: AtomicU64::new(0),
+            over_high_water: AtomicBool::new(false),
+        });
+        let mut registry = REGISTRY.lock().unwrap();
+        registry.retain(|x| x.strong_count() > 0);
+        registry.push(Arc::downgrade(&stats));
+        stats
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn set_high_water(&self, mark: usize) {
+        self.high_water.store(mark, Ordering::Relaxed);
+    }
+    pub fn snapshot(&self) -> ChannelSnapshot {
+        ChannelSnapshot {
+            name: self.name.clone(),
+            capacity: self.capacity,
+            high_water: self.high_water.load(Ordering::Relaxed),
+            sent: self.sent.load(Ordering::Relaxed),
+            received: self.received.load(Ordering::Relaxed),
+            depth: self.depth.load(Ordering::Relaxed),
+            blocked: Duration::from_nanos(self.blocked_nanos.load(Ordering::Relaxed)),
+        }
+    }
+    /// 发送前调用，发送成功后需`SendGuard::sent`，否则drop时回滚计数
+    pub(crate) fn on_send(&self) -> SendGuard<'_> {
+        let depth = self.depth.fetch_add(1, Ordering::Relaxed) + 1;
+        self.sent.fetch_add(1, Ordering::Relaxed);
+        let mark = self.high_water.load(Ordering::Relaxed);
+        if depth >= mark && !self.over_high_water.swap(true, Ordering::Relaxed) {
+            warn!(
+                "channel [{}] depth {} reached high-water mark {} (capacity {})",
+                self.name, depth, mark, self.capacity
+            );
+        }
+        SendGuard { stats: self }
+    }
+    fn on_send_fail(&self) {
+        self.depth.fetch_sub(1, Ordering::Relaxed);
+        self.sent.fetch_sub(1, Ordering::Relaxed);
+    }
+    pub(crate) fn on_recv(&self) {
+        let depth = self.depth.fetch_sub(1, Ordering::Relaxed) - 1;
+        self.received.fetch_add(1, Ordering::Relaxed);
+        if depth < self.high_water.load(Ordering::Relaxed) / 2 {
+            self.over_high_water.store(false, Ordering::Relaxed);
+        }
+    }
+    pub(crate) fn on_blocked(&self, blocked: Duration) {
+        self.blocked_nanos
+            .fetch_add(blocked.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+/// 发送失败或发送中的future被取消时回滚`on_send`的计数
+pub(crate) struct SendGuard<'a> {
+    stats: &'a ChannelStats,
+}
+
+impl SendGuard<'_> {
+    pub(crate) fn sent(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for SendGuard<'_> {
+    fn drop(&mut self) {
+        self.stats.on_send_fail();
+    }
+}
+
+impl Display for ChannelSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] depth {}/{} sent {} received {} blocked {:?}",
+            self.name, self.depth, self.capacity, self.sent, self.received, self.blocked
+        )
+    }
+}
+
+/// 当前仍存活的所有具名通道的状态
+pub fn channel_snapshots() -> Vec<ChannelSnapshot> {
+    let mut registry = REGISTRY.lock().unwrap();
+    registry.retain(|x| x.strong_count() > 0);
+    registry
+        .iter()
+        .filter_map(|x| x.upgrade())
+        .map(|x| x.snapshot())
+        .collect()
+}
+
+/// 带统计的有界同步通道，可直接用于`tx!`/`rx!`
+pub fn channel_named<T>(name: &str, cap: usize) -> (NamedSender<T>, NamedReceiver<T>) {
+    let (tx, rx) = sync_channel(cap);
+    let stats = ChannelStats::register(name, cap);
+    (
+        NamedSender {
+            inner: tx,
+            stats: stats.clone(),
+        },
+        NamedReceiver { inner: rx, stats },
+    )
+}
+
+pub struct NamedSender<T> {
+    inner: SyncSender<T>,
+    stats: Arc<ChannelStats>,
+}
+
+pub struct NamedReceiver<T> {
+    inner: Receiver<T>,
+    stats: Arc<ChannelStats>,
+}
+
+impl<T> Clone for NamedSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<T> NamedSender<T> {
+    pub fn stats(&self) -> &Arc<ChannelStats> {
+        &self.stats
+    }
+    #[doc(hidden)]
+    pub fn watch_name<'a>(&'a self, _expr: &'a str) -> &'a str {
+        self.stats.name()
+    }
+    pub fn send(&self, val: T) -> Result<(), SendError<T>> {
+        let guard = self.stats.on_send();
+        let rs = match self.inner.try_send(val) {
+            Ok(_) => Ok(()),
+            Err(TrySendError::Disconnected(val)) => Err(SendError(val)),
+            Err(TrySendError::Full(val)) => {
+                let start = Instant::now();
+                let rs = self.inner.send(val);
+                self.stats.on_blocked(start.elapsed());
+                rs
+            }
+        };
+        if rs.is_ok() {
+            guard.sent();
+        }
+        rs
+    }
+}
+
+impl<T> NamedReceiver<T> {
+    pub fn stats(&self) -> &Arc<ChannelStats> {
+        &self.stats
+    }
+    #[doc(hidden)]
+    pub fn watch_name<'a>(&'a self, _expr: &'a str) -> &'a str {
+        self.stats.name()
+    }
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let val = self.inner.recv()?;
+        self.stats.on_recv();
+        Ok(val)
+    }
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let val = self.inner.recv_timeout(timeout)?;
+        self.stats.on_recv();
+        Ok(val)
+    }
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let val = self.inner.try_recv()?;
+        self.stats.on_recv();
+        Ok(val)
+    }
+}
+
+impl<T> SendExt<T> for NamedSender<T> {
+    fn send_or_log(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val).map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T> RecvExt<T> for NamedReceiver<T> {
+    fn recv_or_log(&self) -> Result<T, ChannelError> {
+        self.recv().map_err(|_| log_err(ChannelError::Closed))
+    }
+    fn recv_timeout_or_log(&self, timeout: Duration) -> Result<T, ChannelError> {
+        self.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => log_err(ChannelError::Timeout(timeout)),
+            RecvTimeoutError::Disconnected => log_err(ChannelError::Closed),
+        })
+    }
+    fn try_recv_or_log(&self) -> Result<Option<T>, ChannelError> {
+        match self.try_recv() {
+            Ok(val) => Ok(Some(val)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(log_err(ChannelError::Closed)),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::util_txrx::ext::log_err;
+use crate::util_txrx::{AsyncRecvExt, AsyncSendExt, ChannelError, ChannelStats};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::mpsc::{self, error::SendError, error::TryRecvError, error::TrySendError};
+
+/// 带统计的tokio有界通道，可直接用于`tx_async!`/`rx_async!`
+pub fn channel_named_async<T>(
+    name: &str,
+    cap: usize,
+) -> (NamedAsyncSender<T>, NamedAsyncReceiver<T>) {
+    let (tx, rx) = mpsc::channel(cap);
+    let stats = ChannelStats::register(name, cap);
+    (
+        NamedAsyncSender {
+            inner: tx,
+            stats: stats.clone(),
+        },
+        NamedAsyncReceiver { inner: rx, stats },
+    )
+}
+
+pub struct NamedAsyncSender<T> {
+    inner: mpsc::Sender<T>,
+    stats: Arc<ChannelStats>,
+}
+
+pub struct NamedAsyncReceiver<T> {
+    inner: mpsc::Receiver<T>,
+    stats: Arc<ChannelStats>,
+}
+
+impl<T> Clone for NamedAsyncSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<T> NamedAsyncSender<T> {
+    pub fn stats(&self) -> &Arc<ChannelStats> {
+        &self.stats
+    }
+    #[doc(hidden)]
+    pub fn watch_name<'a>(&'a self, _expr: &'a str) -> &'a str {
+        self.stats.name()
+    }
+    pub async fn send(&self, val: T) -> Result<(), SendError<T>> {
+        // future被取消时guard回滚计数
+        let guard = self.stats.on_send();
+        let rs = match self.inner.try_send(val) {
+            Ok(_) => Ok(()),
+            Err(TrySendError::Closed(val)) => Err(SendError(val)),
+            Err(TrySendError::Full(val)) => {
+                let start = Instant::now();
+                let rs = self.inner.send(val).await;
+                self.stats.on_blocked(start.elapsed());
+                rs
+            }
+        };
+        if rs.is_ok() {
+            guard.sent();
+        }
+        rs
+    }
+}
+
+impl<T> NamedAsyncReceiver<T> {
+    pub fn stats(&self) -> &Arc<ChannelStats> {
+        &self.stats
+    }
+    #[doc(hidden)]
+    pub fn watch_name<'a>(&'a self, _expr: &'a str) -> &'a str {
+        self.stats.name()
+    }
+    pub async fn recv(&mut self) -> Option<T> {
+        let val = self.inner.recv().await?;
+        self.stats.on_recv();
+        Some(val)
+    }
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let val = self.inner.try_recv()?;
+        self.stats.on_recv();
+        Ok(val)
+    }
+}
+
+impl<T: Send> AsyncSendExt<T> for NamedAsyncSender<T> {
+    async fn send_or_log_async(&self, val: T) -> Result<(), ChannelError> {
+        self.send(val)
+            .await
+            .map_err(|_| log_err(ChannelError::Closed))
+    }
+}
+
+impl<T: Send> AsyncRecvExt<T> for NamedAsyncReceiver<T> {
+    async fn recv_or_log_async(&mut self) -> Result<T, ChannelError> {
+        self.recv()
+            .await
+            .ok_or_else(|| log_err(ChannelError::Closed))
+    }
+}
@@ -0,0 +1,93 @@
+use crate::util_txrx::ext::log_err;
+use crate::util_txrx::ChannelError;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// 请求/应答通道：`Requester::call`发送请求并等待应答，服务端通过`Responder::recv`处理
+pub fn request_channel<Req, Resp>(cap: usize) -> (Requester<Req, Resp>, Responder<Req, Resp>) {
+    let (tx, rx) = mpsc::channel(cap);
+    (Requester { tx }, Responder { rx })
+}
+
+pub struct Requester<Req, Resp> {
+    tx: mpsc::Sender<Request<Req, Resp>>,
+}
+
+pub struct Responder<Req, Resp> {
+    rx: mpsc::Receiver<Request<Req, Resp>>,
+}
+
+/// 服务端收到的请求，需通过`reply`应答；直接丢弃时调用方得到[`ChannelError::ReplyDropped`]
+pub struct Request<Req, Resp> {
+    req: Req,
+    reply: Reply<Resp>,
+}
+
+pub struct Reply<Resp> {
+    tx: oneshot::Sender<Resp>,
+}
+
+impl<Req, Resp> Clone for Requester<Req, Resp> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<Req, Resp> Requester<Req, Resp> {
+    pub async fn call(&self, req: Req) -> Result<Resp, ChannelError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let request = Request {
+            req,
+            reply: Reply { tx: reply_tx },
+        };
+        if self.tx.send(request).await.is_err() {
+            return Err(log_err(ChannelError::Closed));
+        }
+        reply_rx
+            .await
+            .map_err(|_| log_err(ChannelError::ReplyDropped))
+    }
+    pub async fn call_timeout(&self, req: Req, timeout: Duration) -> Result<Resp, ChannelError> {
+        match tokio::time::timeout(timeout, self.call(req)).await {
+            Ok(rs) => rs,
+            Err(_) => Err(log_err(ChannelError::Timeout(timeout))),
+        }
+    }
+    /// 服务端是否已关闭
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+impl<Req, Resp> Responder<Req, Resp> {
+    /// 所有`Requester`都被丢弃后返回`None`，可直接用于`rx_async!`
+    pub async fn recv(&mut self) -> Option<Request<Req, Resp>> {
+        self.rx.recv().await
+    }
+    pub fn close(&mut self) {
+        self.rx.close()
+    }
+}
+
+impl<Req, Resp> Request<Req, Resp> {
+    pub fn req(&self) -> &Req {
+        &self.req
+    }
+    pub fn into_parts(self) -> (Req, Reply<Resp>) {
+        (self.req, self.reply)
+    }
+    pub fn reply(self, resp: Resp) -> Result<(), ChannelError> {
+        self.reply.send(resp)
+    }
+}
+
+impl<Resp> Reply<Resp> {
+    /// 调用方已放弃等待（如超时）时返回[`ChannelError::Closed`]
+    pub fn send(self, resp: Resp) -> Result<(), ChannelError> {
+        self.tx
+            .send(resp)
+            .map_err(|_| log_err(ChannelError::Closed))
+    }
+}
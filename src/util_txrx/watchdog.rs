@@ -0,0 +1,176 @@
+use log::warn;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::panic::Location;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 阈值（纳秒），0表示未开启
+static THRESHOLD: AtomicU64 = AtomicU64::new(0);
+static CHECKER_STARTED: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static PENDING: Mutex<BTreeMap<u64, PendingOp>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpKind {
+    Send,
+    Recv,
+}
+
+/// 等待时间超过阈值的通道操作
+#[derive(Debug, Clone)]
+pub struct StuckOp {
+    pub channel: String,
+    pub kind: OpKind,
+    pub location: &'static Location<'static>,
+    pub pending: Duration,
+}
+
+struct PendingOp {
+    channel: String,
+    kind: OpKind,
+    location: &'static Location<'static>,
+    start: Instant,
+    warned: bool,
+}
+
+/// 通道操作的登记凭证，drop即视为操作完成
+#[doc(hidden)]
+pub struct OpGuard {
+    id: u64,
+}
+
+impl Drop for OpGuard {
+    fn drop(&mut self) {
+        PENDING.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl Display for OpKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpKind::Send => write!(f, "send"),
+            OpKind::Recv => write!(f, "recv"),
+        }
+    }
+}
+
+impl Display for StuckOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "channel [{}] {} pending {:?} at {}",
+            self.channel, self.kind, self.pending, self.location
+        )
+    }
+}
+
+/// 开启看门狗：通道操作等待超过`threshold`时输出警告。
+/// 对`tx!`/`rx!`/`tx_async!`/`rx_async!`生效，具名通道以通道名登记
+pub fn enable_watchdog(threshold: Duration) {
+    THRESHOLD.store(threshold.as_nanos().max(1) as u64, Ordering::Relaxed);
+    if !CHECKER_STARTED.swap(true, Ordering::Relaxed) {
+        std::thread::Builder::new()
+            .name("channel-watchdog".to_string())
+            .spawn(check_loop)
+            .expect("fail to spawn channel watchdog");
+    }
+}
+
+pub fn disable_watchdog() {
+    THRESHOLD.store(0, Ordering::Relaxed);
+    PENDING.lock().unwrap().clear();
+}
+
+fn threshold() -> Option<Duration> {
+    match THRESHOLD.load(Ordering::Relaxed) {
+        0 => None,
+        nanos => Some(Duration::from_nanos(nanos)),
+    }
+}
+
+fn check_loop() {
+    loop {
+        let interval = match threshold() {
+            Some(threshold) => {
+                let mut pending = PENDING.lock().unwrap();
+                for op in pending.values_mut() {
+                    let elapsed = op.start.elapsed();
+                    if !op.warned && elapsed >= threshold {
+                        op.warned = true;
+                        warn!(
+                            "channel [{}] {} pending {:?} at {}",
+                            op.channel, op.kind, elapsed, op.location
+                        );
+                    }
+                }
+                (threshold / 2).clamp(Duration::from_millis(10), Duration::from_secs(1))
+            }
+            None => Duration::from_secs(1),
+        };
+        std::thread::sleep(interval);
+    }
+}
+
+/// 当前等待超过阈值的通道操作；未开启看门狗时为空
+pub fn stuck_operations() -> Vec<StuckOp> {
+    let threshold = match threshold() {
+        Some(threshold) => threshold,
+        None => return Vec::new(),
+    };
+    PENDING
+        .lock()
+        .unwrap()
+        .values()
+        .filter_map(|op| {
+            let pending = op.start.elapsed();
+            (pending >= threshold).then(|| StuckOp {
+                channel: op.channel.clone(),
+                kind: op.kind,
+                location: op.location,
+                pending,
+            })
+        })
+        .collect()
+}
+
+/// 登记一次通道操作；未开启看门狗时返回`None`
+#[doc(hidden)]
+#[track_caller]
+pub fn watch_op(channel: &str, kind: OpKind) -> Option<OpGuard> {
+    threshold()?;
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    PENDING.lock().unwrap().insert(
+        id,
+        PendingOp {
+            channel: channel.to_string(),
+            kind,
+            location: Location::caller(),
+            start: Instant::now(),
+            warned: false,
+        },
+    );
+    Some(OpGuard { id })
+}
+
+/// 宏登记通道操作时使用的名称，默认为通道表达式的文本；
+/// 具名通道以同名的inherent方法返回通道名
+#[doc(hidden)]
+pub trait WatchName {
+    fn watch_name<'a>(&'a self, expr: &'a str) -> &'a str {
+        expr
+    }
+}
+
+impl<T: ?Sized> WatchName for T {}
+
+/// `rx_async!`只求值一次通道表达式：方法调用的自动借用使`&mut`参数无需`mut`绑定
+#[doc(hidden)]
+pub trait ChannelMut {
+    fn __channel_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
+impl<T: ?Sized> ChannelMut for T {}
@@ -0,0 +1,67 @@
+use custom_utils::actor::{spawn_actor, Actor, Supervisor};
+use custom_utils::txrx::ChannelError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+struct Counter {
+    count: u32,
+    started: Arc<AtomicUsize>,
+}
+
+enum Msg {
+    Add(u32),
+    Get,
+    Panic,
+}
+
+impl Actor for Counter {
+    type Msg = Msg;
+    type Reply = u32;
+
+    async fn handle(&mut self, msg: Msg) -> u32 {
+        match msg {
+            Msg::Add(n) => self.count += n,
+            Msg::Get => {}
+            Msg::Panic => panic!("boom"),
+        }
+        self.count
+    }
+    async fn started(&mut self) {
+        self.started.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn test_actor() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let addr = spawn_actor(
+        "counter",
+        Counter {
+            count: 0,
+            started: started.clone(),
+        },
+        4,
+    );
+    addr.send(Msg::Add(2)).await.unwrap();
+    assert_eq!(addr.call(Msg::Add(3)).await, Ok(5));
+    assert_eq!(addr.call(Msg::Panic).await, Err(ChannelError::ReplyDropped));
+    assert_eq!(addr.call(Msg::Get).await, Err(ChannelError::Closed));
+}
+
+#[tokio::test]
+async fn test_supervisor() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let factory_started = started.clone();
+    let addr = Supervisor::new("supervised")
+        .backoff(Duration::from_millis(10), Duration::from_millis(100))
+        .spawn(move || Counter {
+            count: 0,
+            started: factory_started.clone(),
+        });
+    assert_eq!(addr.call(Msg::Add(3)).await, Ok(3));
+    assert_eq!(addr.call(Msg::Panic).await, Err(ChannelError::ReplyDropped));
+    // 重启后状态重新初始化
+    assert_eq!(addr.call(Msg::Add(1)).await, Ok(1));
+    assert_eq!(started.load(Ordering::SeqCst), 2);
+}
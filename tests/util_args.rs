@@ -0,0 +1,397 @@
+use custom_utils::args::{ArgError, ArgReader, Args, Cli, Matches, Opt, Parsed, Shell};
+
+#[test]
+fn test_gnu_forms() {
+    let args =
+        Args::new(["dns", "--config=./a.yaml", "-lv", "-p53", "--", "-x"]).value_shorts("cp");
+    assert_eq!(args.command(), Some("dns".to_string()));
+    assert_eq!(
+        args.arg_value("--config", "-c"),
+        Some("./a.yaml".to_string())
+    );
+    assert_eq!(args.arg_value_as::<u16>("--port", "-p"), Ok(Some(53)));
+    assert!(args.exist_arg("--loop", "-l"));
+    assert!(args.exist_arg("verbose", "v"));
+    assert!(!args.exist_arg("--x", "-x"));
+}
+
+#[test]
+fn test_short_clusters() {
+    let args = Args::new(["-c/etc/app.yaml"]);
+    assert!(!args.exist_arg("--loop", "-l"));
+    assert_eq!(
+        args.arg_value("--config", "-c"),
+        Some("/etc/app.yaml".to_string())
+    );
+
+    // 未声明时只有第一个字符是选项
+    let args = Args::new(["-cpath", "-lv"]);
+    assert_eq!(args.arg_value("--all", "-a"), None);
+    assert_eq!(args.arg_value("--config", "-c"), Some("path".to_string()));
+    for short in ["-p", "-a", "-t", "-h", "-v"] {
+        assert!(!args.exist_arg("", short), "{}", short);
+    }
+    assert!(args.exist_arg("--loop", "-l"));
+    assert!(Args::new(["-lv"])
+        .value_shorts("")
+        .exist_arg("--verbose", "-v"));
+
+    let args = Args::new(["-lvcpath", "-Ia", "-I", "b"]).value_shorts("cI");
+    assert!(args.exist_arg("--loop", "-l"));
+    assert!(args.exist_arg("--verbose", "-v"));
+    assert!(!args.exist_arg("--all", "-a"));
+    assert!(!args.exist_arg("--path", "-p"));
+    assert_eq!(args.arg_value("--config", "-c"), Some("path".to_string()));
+    assert_eq!(args.arg_value("--all", "-a"), None);
+    assert!(args.arg_value_as::<String>("--loop", "-l").is_err());
+    assert_eq!(
+        args.arg_values("--include", "-I"),
+        Ok(vec!["a".to_string(), "b".to_string()])
+    );
+}
+
+#[test]
+fn test_typed_values() {
+    let args = Args::new(["-I", "a", "--include", "b", "-Ic", "--port", "http", "-t"]);
+    assert_eq!(
+        args.arg_values("--include", "-I"),
+        Ok(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+    );
+    assert_eq!(args.arg_value_or("--level", "", 3u8), Ok(3));
+    assert_eq!(
+        args.arg_value_required::<u32>("--id", ""),
+        Err(ArgError::Missing {
+            flag: "--id".to_string()
+        })
+    );
+    assert!(matches!(
+        args.arg_value_as::<u16>("--port", "-p"),
+        Err(ArgError::Parse { flag, value, .. }) if flag == "--port/-p" && value == "http"
+    ));
+    assert_eq!(
+        args.arg_value_as::<u64>("--timeout", "-t"),
+        Err(ArgError::MissingValue {
+            flag: "--timeout/-t".to_string()
+        })
+    );
+    assert!(matches!(
+        args.arg_value_as::<u64>("--timeout", "-tt"),
+        Err(ArgError::InvalidName(_))
+    ));
+}
+
+#[test]
+fn test_reader_collects_errors() {
+    let args = Args::new(["--port", "x", "-n", "2"]);
+    let mut reader = ArgReader::from_args(args);
+    let port: u16 = reader.required("--port", "-p");
+    let host: String = reader.required("--host", "");
+    let num: u8 = reader.required("--num", "-n");
+    assert_eq!((port, host.as_str(), num), (0, "", 2));
+    let err = reader.finish().unwrap_err();
+    assert_eq!(err.0.len(), 2);
+    let text = err.to_string();
+    assert!(text.contains("--port/-p"), "{}", text);
+    assert!(text.contains("missing required option --host"), "{}", text);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_non_utf8() {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+    let path = OsString::from_vec(vec![b'a', 0xff, b'b']);
+    let args = Args::new([OsString::from("-c"), path.clone(), OsString::from("-l")]);
+    assert_eq!(args.arg_value_os("--config", "-c"), Some(path));
+    assert_eq!(
+        args.arg_value("--config", "-c"),
+        Some("a\u{fffd}b".to_string())
+    );
+    assert!(args.exist_arg("--loop", "-l"));
+}
+
+fn dns_cli() -> Cli {
+    Cli::new("dns")
+        .version("1.2.0")
+        .about("a tiny dns server")
+        .opt(Opt::value("--config", "-c", "PATH").help("config file"))
+        .opt(
+            Opt::value("--port", "-p", "PORT")
+                .help("listen port")
+                .default("53"),
+        )
+        .opt(Opt::flag("--loop", "-l").help("run forever"))
+        .opt(Opt::flag("--debug", "").hidden())
+}
+
+fn matches(cli: &Cli, args: &[&str]) -> Matches {
+    match cli.try_parse(Args::new(args)).unwrap() {
+        Parsed::Matches(matches) => matches,
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn test_cli_matches() {
+    let cli = dns_cli();
+    let m = matches(&cli, &["-lcpath", "run", "--", "--loop"]);
+    assert_eq!(m.value("config"), Some("path".to_string()));
+    assert!(m.args().exist_arg("--loop", "-l"));
+    assert!(!m.args().exist_arg("", "-p"));
+    assert_eq!(
+        m.args().arg_value("--config", "-c"),
+        Some("path".to_string())
+    );
+    assert!(m.flag("-l"));
+    assert!(!m.flag("--debug"));
+    assert_eq!(m.value_as::<u16>("port"), Ok(Some(53)));
+    assert_eq!(m.positionals(), ["run".to_string(), "--loop".to_string()]);
+
+    let m = matches(&cli, &["-p", "5353", "--port=53"]);
+    assert_eq!(m.values_as::<u16>("--port"), Ok(vec![5353, 53]));
+}
+
+#[test]
+fn test_cli_errors() {
+    let err = dns_cli()
+        .try_parse(Args::new(["--confg", "a", "-x", "--loop=1", "-p"]))
+        .unwrap_err();
+    assert_eq!(
+        err.0,
+        vec![
+            ArgError::Unknown {
+                flag: "--confg".to_string(),
+                suggestion: Some("--config".to_string()),
+            },
+            ArgError::Unknown {
+                flag: "-x".to_string(),
+                suggestion: None,
+            },
+            ArgError::UnexpectedValue {
+                flag: "--loop/-l".to_string()
+            },
+            ArgError::MissingValue {
+                flag: "--port/-p".to_string()
+            },
+        ]
+    );
+    assert!(err.to_string().contains("did you mean --config?"));
+}
+
+#[test]
+fn test_cli_help() {
+    let cli = dns_cli();
+    let Parsed::Help(help) = cli.try_parse(Args::new(["-h"])).unwrap() else {
+        panic!()
+    };
+    assert!(
+        help.starts_with("dns 1.2.0\na tiny dns server\n"),
+        "{}",
+        help
+    );
+    assert!(
+        help.contains("  -c, --config <PATH>  config file\n"),
+        "{}",
+        help
+    );
+    assert!(
+        help.contains("  -p, --port <PORT>    listen port [default: 53]\n"),
+        "{}",
+        help
+    );
+    assert!(
+        help.contains("  -l, --loop           run forever\n"),
+        "{}",
+        help
+    );
+    assert!(!help.contains("--debug"));
+    let Parsed::Version(version) = cli.try_parse(Args::new(["--version"])).unwrap() else {
+        panic!()
+    };
+    assert_eq!(version, "dns 1.2.0");
+}
+
+#[test]
+fn test_subcommands() {
+    use std::sync::{Arc, Mutex};
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let record = seen.clone();
+    let cli = Cli::new("ctl")
+        .opt(Opt::flag("--verbose", "-v"))
+        .subcommand(
+            Cli::new("service")
+                .about("manage services")
+                .subcommand(
+                    Cli::new("start")
+                        .about("start a service")
+                        .opt(Opt::value("--port", "-p", "PORT").default("80"))
+                        .handler(move |m| {
+                            let port: u16 = m.value_as("port")?.unwrap();
+                            record
+                                .lock()
+                                .unwrap()
+                                .push((m.positionals().to_vec(), port));
+                            Ok(())
+                        }),
+                )
+                .subcommand(Cli::new("stop")),
+        );
+
+    let m = matches(&cli, &["-v", "service", "start", "-p", "8080", "web"]);
+    assert!(m.flag("verbose"));
+    let (name, service) = m.subcommand().unwrap();
+    assert_eq!(name, "service");
+    assert_eq!(service.subcommand().unwrap().1.args().len(), 3);
+    cli.dispatch(&m).unwrap();
+    assert_eq!(
+        seen.lock().unwrap().as_slice(),
+        [(vec!["web".to_string()], 8080)]
+    );
+
+    // 没有处理函数的stop视为空操作，缺少子命令则报错
+    cli.dispatch(&matches(&cli, &["service", "stop"])).unwrap();
+    let err = cli.dispatch(&matches(&cli, &["service"])).unwrap_err();
+    assert!(err.to_string().contains("start, stop"), "{}", err);
+
+    let err = cli
+        .try_parse(Args::new(["service", "strat", "--port", "1"]))
+        .unwrap_err();
+    assert_eq!(
+        err.0,
+        vec![ArgError::UnknownCommand {
+            command: "strat".to_string(),
+            suggestion: Some("start".to_string()),
+            available: vec!["start".to_string(), "stop".to_string()],
+        }]
+    );
+
+    let Parsed::Help(help) = cli.try_parse(Args::new(["service", "-h"])).unwrap() else {
+        panic!()
+    };
+    assert!(
+        help.starts_with("ctl service\nmanage services\n"),
+        "{}",
+        help
+    );
+    assert!(
+        help.contains("  start  start a service\n  stop\n"),
+        "{}",
+        help
+    );
+}
+
+#[test]
+fn test_completions() {
+    let cli = dns_cli()
+        .opt(Opt::value("--log", "", "FILE").path().help("log: file"))
+        .subcommand(Cli::new("start").about("start the 'server'"));
+    let Parsed::Completions(bash) = cli
+        .try_parse(Args::new(["--generate-completions", "bash"]))
+        .unwrap()
+    else {
+        panic!()
+    };
+    assert_eq!(bash, cli.completions(Shell::Bash));
+    assert!(bash.contains("--log) COMPREPLY=($(compgen -f -- \"${cur}\")); return ;;"));
+    assert!(bash.contains("\"dns:start\") cmd=\"dns start\" ;;"));
+    assert!(!bash.contains("--debug") && !bash.contains("generate-completions"));
+
+    let zsh = cli.completions(Shell::Zsh);
+    assert!(zsh.starts_with("#compdef dns\n"));
+    assert!(zsh.contains("--log) _files; return ;;"));
+    assert!(zsh.contains("'--log:log\\: file'"));
+
+    let fish = cli.completions(Shell::Fish);
+    assert!(fish.contains("complete -c dns -n 'not __fish_seen_subcommand_from start' -l config -s c -x -d 'config file'"));
+    assert!(fish.contains("-a start -d 'start the \\'server\\''"));
+    assert!(fish.contains("-l log -r -F"));
+
+    assert!(cli
+        .try_parse(Args::new(["--generate-completions", "tcsh"]))
+        .is_err());
+    let Parsed::Help(help) = cli.try_parse(Args::new(["--help"])).unwrap() else {
+        panic!()
+    };
+    assert!(!help.contains("generate-completions"));
+}
+
+#[test]
+fn test_constraints() {
+    let cli = Cli::new("tls")
+        .opt(Opt::value("--cert", "", "PATH").requires("--key"))
+        .opt(Opt::value("--key", "", "PATH"))
+        .opt(Opt::flag("--loop", "-l").conflicts_with("once"))
+        .opt(Opt::flag("--once", ""))
+        .opt(Opt::value("--port", "-p", "PORT").range(1..=65535))
+        .opt(Opt::value("--name", "-n", "NAME").required())
+        .opt(Opt::value("--mode", "", "MODE").required().default("a"))
+        .opt(Opt::flag("--tcp", ""))
+        .opt(Opt::flag("--udp", ""))
+        .one_of(&["--tcp", "--udp"]);
+    let err = cli
+        .try_parse(Args::new([
+            "--cert", "a.pem", "-l", "--once", "-p", "70000",
+        ]))
+        .unwrap_err();
+    assert_eq!(
+        err.0,
+        vec![
+            ArgError::Requires {
+                flag: "--cert".to_string(),
+                required: "--key".to_string(),
+            },
+            ArgError::Conflict {
+                flag: "--loop/-l".to_string(),
+                other: "--once".to_string(),
+            },
+            ArgError::OutOfRange {
+                flag: "--port/-p".to_string(),
+                value: "70000".to_string(),
+                min: 1,
+                max: 65535,
+            },
+            ArgError::Missing {
+                flag: "--name/-n".to_string()
+            },
+            ArgError::OneOf {
+                flags: vec!["--tcp".to_string(), "--udp".to_string()],
+                found: 0,
+            },
+        ]
+    );
+    let text = err.to_string();
+    assert_eq!(text.lines().count(), 5, "{}", text);
+    assert!(text.contains("error: option --loop/-l cannot be used with --once"));
+
+    assert!(cli
+        .try_parse(Args::new(["-n", "x", "--tcp", "--udp"]))
+        .is_err());
+    // 约束中的名称须已声明
+    let err = Cli::new("typo")
+        .opt(Opt::flag("--loop", "-l").conflicts_with("onse"))
+        .opt(Opt::flag("--once", "").requires("-l"))
+        .opt(Opt::flag("--tcp", ""))
+        .one_of(&["--tcp", "--upd"])
+        .try_parse(Args::new(["--tcp"]))
+        .unwrap_err();
+    assert_eq!(
+        err.0,
+        vec![
+            ArgError::InvalidName("--onse (in constraints of --loop/-l)".to_string()),
+            ArgError::InvalidName("--l (in constraints of --once)".to_string()),
+            ArgError::InvalidName("--upd (in one_of)".to_string()),
+        ]
+    );
+    // 不是整数时是解析错误而非超出范围
+    let err = cli
+        .try_parse(Args::new(["-n", "x", "--tcp", "-p", "abc"]))
+        .unwrap_err();
+    assert!(matches!(
+        err.0.as_slice(),
+        [ArgError::Parse { flag, value, .. }] if flag == "--port/-p" && value == "abc"
+    ));
+    matches(
+        &cli,
+        &["-n", "x", "--udp", "--cert=a", "--key=b", "-p", "443"],
+    );
+}
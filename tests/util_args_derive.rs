@@ -0,0 +1,101 @@
+use custom_utils::args::{ArgError, Args, CustomArgs, Parsed};
+use std::path::PathBuf;
+
+/// a tiny dns server
+#[derive(CustomArgs, Debug, PartialEq)]
+#[args(name = "dns", version = "1.2.0")]
+struct Opts {
+    /// config file
+    #[arg(short = 'c', value_name = "PATH", path)]
+    config: Option<PathBuf>,
+    /// listen port
+    #[arg(short, default = "53")]
+    port: u16,
+    /// upstream servers,
+    /// repeatable
+    #[arg(short = 'u')]
+    upstream: Vec<String>,
+    /// run forever
+    #[arg(short = 'l', long = "loop")]
+    forever: bool,
+    /// instance name
+    name: String,
+}
+
+fn parse(args: &[&str]) -> Result<Opts, custom_utils::args::ArgErrors> {
+    match Opts::cli().try_parse(Args::new(args))? {
+        Parsed::Matches(matches) => Opts::from_matches(&matches),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn test_derive() {
+    let opts = parse(&["-lc", "a.toml", "-u", "1.1.1.1", "-u8.8.8.8", "--name", "x"]).unwrap();
+    assert_eq!(
+        opts,
+        Opts {
+            config: Some(PathBuf::from("a.toml")),
+            port: 53,
+            upstream: vec!["1.1.1.1".to_string(), "8.8.8.8".to_string()],
+            forever: true,
+            name: "x".to_string(),
+        }
+    );
+
+    // 类型转换错误与缺少的选项一并返回
+    let err = parse(&["-p", "http"]).unwrap_err();
+    assert!(matches!(
+        err.0.as_slice(),
+        [ArgError::Parse { flag, .. }, ArgError::Missing { flag: missing }]
+            if flag == "--port/-p" && missing == "--name"
+    ));
+    let matches = match Opts::cli().try_parse(Args::new(["-p", "http", "--name", "x"])) {
+        Ok(Parsed::Matches(matches)) => matches,
+        other => panic!("{:?}", other),
+    };
+    assert!(matches!(
+        Opts::from_matches(&matches).unwrap_err().0.as_slice(),
+        [ArgError::Parse { flag, .. }] if flag == "--port/-p"
+    ));
+}
+
+#[test]
+fn test_derive_help() {
+    let help = Opts::cli().help();
+    assert!(
+        help.starts_with("dns 1.2.0\na tiny dns server\n"),
+        "{}",
+        help
+    );
+    let options = "
+  -c, --config <PATH>        config file
+  -p, --port <PORT>          listen port [default: 53]
+  -u, --upstream <UPSTREAM>  upstream servers, repeatable
+  -l, --loop                 run forever
+      --name <NAME>          instance name
+";
+    assert!(help.contains(options), "{}", help);
+}
+
+#[derive(CustomArgs)]
+#[args(name = "shorts")]
+struct Shorts {
+    #[arg(long = "verbose", short)]
+    debug: bool,
+    #[arg(short, long = "quiet")]
+    silent: bool,
+}
+
+/// 未指定字符的`short`取字段名首字母，与属性顺序无关
+#[test]
+fn test_derive_short() {
+    let help = Shorts::cli().help();
+    assert!(help.contains("  -d, --verbose\n"), "{}", help);
+    assert!(help.contains("  -s, --quiet\n"), "{}", help);
+    let Ok(Parsed::Matches(matches)) = Shorts::cli().try_parse(Args::new(["-ds"])) else {
+        panic!()
+    };
+    let shorts = Shorts::from_matches(&matches).unwrap();
+    assert!(shorts.debug && shorts.silent);
+}
@@ -0,0 +1,70 @@
+use custom_utils::args::Args;
+use custom_utils::settings::{SettingsResolver, Source};
+use std::path::PathBuf;
+
+#[test]
+fn test_layers() {
+    let dir = std::env::temp_dir().join(format!("settings-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("app.toml");
+    std::fs::write(
+        &path,
+        "name = \"from-file\"\n[server]\nport = 8000\nhost = \"file-host\"\n",
+    )
+    .unwrap();
+    std::env::set_var("LAYERTEST_SERVER_HOST", "env-host");
+    std::env::set_var("LAYERTEST_SERVER_PORT", "9000");
+
+    let args = Args::new([
+        "--server-port".to_string(),
+        "7000".to_string(),
+        "-c".to_string(),
+        path.display().to_string(),
+    ]);
+    let settings = SettingsResolver::new("layertest")
+        .args(args)
+        .key("server.port", "-p", Some("1"))
+        .key("server.host", "", None)
+        .key("name", "", None)
+        .key("timeout", "-t", Some("3"))
+        .key("missing", "", None)
+        .resolve()
+        .unwrap();
+
+    assert_eq!(settings.config_file(), Some(path.as_path()));
+    assert_eq!(settings.get_as::<u16>("server.port").unwrap(), Some(7000));
+    assert_eq!(
+        settings.source("server.port"),
+        Some(&Source::Cli("--server-port".to_string()))
+    );
+    assert_eq!(settings.get("server.host"), Some("env-host"));
+    assert_eq!(
+        settings.source("server.host"),
+        Some(&Source::Env("LAYERTEST_SERVER_HOST".to_string()))
+    );
+    assert_eq!(settings.get("name"), Some("from-file"));
+    assert_eq!(settings.source("name"), Some(&Source::File(path.clone())));
+    assert_eq!(settings.required::<u64>("timeout").unwrap(), 3);
+    assert_eq!(settings.source("timeout"), Some(&Source::Default));
+    assert!(settings.required::<String>("missing").is_err());
+    assert!(settings.get_as::<u8>("server.port").is_err());
+
+    let dump = settings.dump();
+    assert!(
+        dump.contains("server.port = 7000 (cli --server-port)"),
+        "{}",
+        dump
+    );
+    assert!(dump.contains("missing     = <unset>"), "{}", dump);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_missing_explicit_config() {
+    let rs = SettingsResolver::new("missing-config")
+        .args(Args::default())
+        .config_file(PathBuf::from("/nonexistent/config.toml"))
+        .key("a", "", None)
+        .resolve();
+    assert!(rs.is_err());
+}
@@ -0,0 +1,34 @@
+use custom_utils::shutdown::Shutdown;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_drain() {
+    let shutdown = Shutdown::new();
+    let guard = shutdown.register("graceful");
+    tokio::spawn(async move {
+        guard.token().cancelled().await;
+        drop(guard);
+    });
+    let stuck = shutdown.register("stuck");
+    let stuck_token = stuck.token();
+
+    assert!(!stuck_token.is_shutdown());
+    let unfinished = shutdown.drain(Duration::from_millis(100)).await;
+    assert!(stuck_token.is_shutdown());
+    assert_eq!(unfinished, vec!["stuck".to_string()]);
+    drop(stuck);
+    assert!(shutdown.pending_tasks().is_empty());
+}
+
+#[test]
+fn test_drain_blocking() {
+    let shutdown = Shutdown::new();
+    let guard = shutdown.register("thread");
+    let handle = std::thread::spawn(move || {
+        let token = guard.token();
+        while !token.wait_timeout(Duration::from_millis(10)) {}
+    });
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(shutdown.drain_blocking(Duration::from_secs(1)).is_empty());
+    handle.join().unwrap();
+}
@@ -0,0 +1,519 @@
+use anyhow::{bail, Result};
+use custom_utils::txrx::{
+    async_to_sync, bounded, channel_named, channel_named_async, channel_snapshots, durable_queue,
+    enable_watchdog, request_channel, stuck_operations, sync_to_async, AsyncRecvExt, AsyncSendExt,
+    Batch, ChannelError, OpKind, OverflowPolicy, RecvExt, SendExt, SendOnceExt,
+};
+use custom_utils::{rx, rx_async, rx_batch, rx_batch_async, tx, tx_async};
+use log::error;
+use std::io::Write;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_millis(50);
+
+fn rx_timeout() -> Result<u32> {
+    let (_tx, rx) = channel::<u32>();
+    Ok(rx!(rx, timeout = TIMEOUT))
+}
+fn rx_timeout_closed() -> Result<u32> {
+    let (tx, rx) = channel::<u32>();
+    drop(tx);
+    Ok(rx!(rx, timeout = TIMEOUT, "receive fail"))
+}
+async fn rx_async_timeout() -> Result<u32> {
+    let (_tx, mut rx) = tokio::sync::mpsc::channel::<u32>(3);
+    Ok(rx_async!(rx, timeout = TIMEOUT))
+}
+async fn rx_async_timeout_closed() -> Result<u32> {
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<u32>(3);
+    drop(tx);
+    Ok(rx_async!(rx, timeout = TIMEOUT, "async receive fail"))
+}
+async fn tx_async_timeout() -> Result<()> {
+    let (tx, _rx) = tokio::sync::mpsc::channel::<u32>(1);
+    tx_async!(tx, 1, timeout = TIMEOUT);
+    tx_async!(tx, 2, timeout = TIMEOUT);
+    Ok(())
+}
+
+#[test]
+fn test_rx_timeout() {
+    custom_utils::logger::logger_stdout_debug();
+    let err = rx_timeout().unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&ChannelError::Timeout(TIMEOUT)));
+    let err = rx_timeout_closed().unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&ChannelError::Closed));
+}
+
+#[tokio::test]
+async fn test_async_timeout() {
+    custom_utils::logger::logger_stdout_debug();
+    let err = rx_async_timeout().await.unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&ChannelError::Timeout(TIMEOUT)));
+    let err = rx_async_timeout_closed().await.unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&ChannelError::Closed));
+    let err = tx_async_timeout().await.unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&ChannelError::Timeout(TIMEOUT)));
+}
+
+#[test]
+fn test_ext_std() {
+    let (tx, rx) = channel::<u32>();
+    tx.send_or_log(1).unwrap();
+    assert_eq!(rx.recv_or_log(), Ok(1));
+    assert_eq!(rx.try_recv_or_log(), Ok(None));
+    assert_eq!(
+        rx.recv_timeout_or_log(TIMEOUT),
+        Err(ChannelError::Timeout(TIMEOUT))
+    );
+    drop(tx);
+    assert_eq!(rx.recv_or_log(), Err(ChannelError::Closed));
+}
+
+#[tokio::test]
+async fn test_ext_tokio() {
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<u32>(1);
+    tx.send_or_log_async(1).await.unwrap();
+    assert_eq!(
+        tx.send_timeout_or_log_async(2, TIMEOUT).await,
+        Err(ChannelError::Timeout(TIMEOUT))
+    );
+    assert_eq!(rx.recv_or_log_async().await, Ok(1));
+    drop(tx);
+    assert_eq!(rx.recv_or_log_async().await, Err(ChannelError::Closed));
+
+    let (tx, mut rx) = tokio::sync::broadcast::channel::<u32>(1);
+    tx.send_or_log(1).unwrap();
+    tx.send_or_log(2).unwrap();
+    assert_eq!(rx.recv_or_log_async().await, Err(ChannelError::Lagged(1)));
+    assert_eq!(rx.recv_or_log_async().await, Ok(2));
+
+    let (tx, mut rx) = tokio::sync::watch::channel::<u32>(0);
+    tx.send_or_log(3).unwrap();
+    assert_eq!(rx.recv_or_log_async().await, Ok(3));
+
+    let (tx, mut rx) = tokio::sync::oneshot::channel::<u32>();
+    tx.send_or_log(4).unwrap();
+    assert_eq!(rx.recv_or_log_async().await, Ok(4));
+    assert_eq!(rx.recv_or_log_async().await, Err(ChannelError::Closed));
+    let (tx, mut rx) = tokio::sync::oneshot::channel::<u32>();
+    drop(tx);
+    assert_eq!(rx.recv_or_log_async().await, Err(ChannelError::Closed));
+    assert_eq!(rx.recv_or_log_async().await, Err(ChannelError::Closed));
+}
+
+#[tokio::test]
+async fn test_channel_named() {
+    let (tx, rx) = channel_named::<u32>("test_named_sync", 2);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    let snapshot = tx.stats().snapshot();
+    assert_eq!((snapshot.sent, snapshot.depth), (2, 2));
+    assert_eq!(rx.recv().unwrap(), 1);
+
+    let (tx, mut rx) = channel_named_async::<u32>("test_named_async", 1);
+    tx.send(1).await.unwrap();
+    let handle = tokio::spawn(async move {
+        tokio::time::sleep(TIMEOUT).await;
+        (rx.recv().await, rx)
+    });
+    tx.send(2).await.unwrap();
+    assert_eq!(handle.await.unwrap().0, Some(1));
+    let snapshot = tx.stats().snapshot();
+    assert_eq!(
+        (snapshot.sent, snapshot.received, snapshot.depth),
+        (2, 1, 1)
+    );
+    assert!(snapshot.blocked >= TIMEOUT / 2);
+
+    // 阻塞中的发送被取消后回滚计数
+    let (tx, mut rx) = channel_named_async::<u32>("test_named_cancel", 1);
+    tx.send(1).await.unwrap();
+    assert!(tokio::time::timeout(TIMEOUT, tx.send(2)).await.is_err());
+    let snapshot = tx.stats().snapshot();
+    assert_eq!((snapshot.sent, snapshot.depth), (1, 1));
+    assert_eq!(rx.recv().await, Some(1));
+    assert_eq!(tx.stats().snapshot().depth, 0);
+
+    let names: Vec<String> = channel_snapshots().into_iter().map(|x| x.name).collect();
+    assert!(names.contains(&"test_named_sync".to_string()));
+    assert!(names.contains(&"test_named_async".to_string()));
+}
+
+#[tokio::test]
+async fn test_bounded_policy() {
+    let (tx, rx) = bounded::<u32>(2, OverflowPolicy::DropNewest);
+    for i in 0..4 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(tx.dropped(), 2);
+    assert_eq!((rx.recv(), rx.recv()), (Ok(0), Ok(1)));
+
+    let (tx, rx) = bounded::<u32>(2, OverflowPolicy::DropOldest);
+    for i in 0..4 {
+        tx.send_async(i).await.unwrap();
+    }
+    assert_eq!(rx.dropped(), 2);
+    assert_eq!(
+        (rx.recv_async().await, rx.recv_async().await),
+        (Ok(2), Ok(3))
+    );
+
+    let (tx, rx) = bounded::<u32>(1, OverflowPolicy::ErrorAfter(TIMEOUT));
+    tx.send(0).unwrap();
+    assert_eq!(tx.send(1), Err(ChannelError::Timeout(TIMEOUT)));
+    assert_eq!(tx.send_async(1).await, Err(ChannelError::Timeout(TIMEOUT)));
+
+    let (tx, rx_block) = bounded::<u32>(1, OverflowPolicy::Block);
+    tx.send(0).unwrap();
+    let handle = std::thread::spawn(move || {
+        std::thread::sleep(TIMEOUT);
+        let first = rx_block.recv();
+        (first, rx_block.recv())
+    });
+    tx.send(1).unwrap();
+    drop(tx);
+    let (first, second) = handle.join().unwrap();
+    assert_eq!((first, second), (Ok(0), Ok(1)));
+    drop(rx);
+}
+
+/// 消息中持有同一通道的发送端，丢弃消息时不能在持锁状态下drop
+struct Holder(#[allow(dead_code)] custom_utils::txrx::BoundedSender<Holder>);
+
+#[test]
+fn test_bounded_drop_without_lock() {
+    for policy in [OverflowPolicy::DropNewest, OverflowPolicy::DropOldest] {
+        let (tx, rx) = bounded::<Holder>(1, policy);
+        tx.send(Holder(tx.clone())).unwrap();
+        tx.send(Holder(tx.clone())).unwrap();
+        assert_eq!(tx.dropped(), 1);
+        drop(rx);
+        assert!(tx.send(Holder(tx.clone())).is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_request_channel() {
+    let (requester, mut responder) = request_channel::<u32, u32>(4);
+    let service = tokio::spawn(async move {
+        while let Some(request) = responder.recv().await {
+            match *request.req() {
+                0 => drop(request),
+                1 => tokio::time::sleep(TIMEOUT * 2).await,
+                n => request.reply(n * 2).unwrap(),
+            }
+        }
+    });
+    assert_eq!(requester.call(2).await, Ok(4));
+    assert_eq!(requester.call(0).await, Err(ChannelError::ReplyDropped));
+    assert_eq!(
+        requester.call_timeout(1, TIMEOUT).await,
+        Err(ChannelError::Timeout(TIMEOUT))
+    );
+    service.abort();
+    let _ = service.await;
+    assert_eq!(requester.call(3).await, Err(ChannelError::Closed));
+}
+
+fn batch_sync(rx: &std::sync::mpsc::Receiver<u32>) -> Result<Batch<u32>> {
+    Ok(rx_batch!(rx, 3, TIMEOUT))
+}
+async fn batch_async(rx: &mut tokio::sync::mpsc::Receiver<u32>) -> Result<Batch<u32>> {
+    Ok(rx_batch_async!(rx, 3, TIMEOUT, "batch receive fail"))
+}
+
+#[tokio::test]
+async fn test_rx_batch() {
+    let (tx, rx) = channel::<u32>();
+    for i in 0..4 {
+        tx.send(i).unwrap();
+    }
+    let batch = batch_sync(&rx).unwrap();
+    assert_eq!((batch.items, batch.closed), (vec![0, 1, 2], false));
+    let batch = batch_sync(&rx).unwrap();
+    assert_eq!((batch.items, batch.closed), (vec![3], false));
+    tx.send(4).unwrap();
+    drop(tx);
+    let batch = batch_sync(&rx).unwrap();
+    assert_eq!((batch.items, batch.closed), (vec![4], true));
+    assert!(batch_sync(&rx).is_err());
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<u32>(8);
+    for i in 0..2 {
+        tx.send(i).await.unwrap();
+    }
+    let batch = batch_async(&mut rx).await.unwrap();
+    assert_eq!((batch.items, batch.closed), (vec![0, 1], false));
+    tx.send(2).await.unwrap();
+    drop(tx);
+    let batch = batch_async(&mut rx).await.unwrap();
+    assert_eq!((batch.items, batch.closed), (vec![2], true));
+    assert!(batch_async(&mut rx).await.is_err());
+}
+
+/// `wait`自调用起计时，空闲的通道到时返回空批次
+#[tokio::test]
+async fn test_rx_batch_idle() {
+    let (_tx, rx) = channel::<u32>();
+    let start = std::time::Instant::now();
+    let batch = batch_sync(&rx).unwrap();
+    assert_eq!((batch.items, batch.closed), (vec![], false));
+    assert!(start.elapsed() < TIMEOUT * 4);
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<u32>(8);
+    let start = std::time::Instant::now();
+    let batch = batch_async(&mut rx).await.unwrap();
+    assert_eq!((batch.items, batch.closed), (vec![], false));
+    // 第一条消息晚到时，批次仍在调用后的`wait`内返回
+    let sender = tokio::spawn(async move {
+        tokio::time::sleep(TIMEOUT * 3 / 4).await;
+        tx.send(1).await.unwrap();
+        tokio::time::sleep(TIMEOUT).await;
+        tx.send(2).await.unwrap();
+    });
+    let start_late = std::time::Instant::now();
+    let batch = batch_async(&mut rx).await.unwrap();
+    assert_eq!(batch.items, vec![1]);
+    assert!(start_late.elapsed() < TIMEOUT * 3 / 2);
+    assert!(start.elapsed() >= TIMEOUT);
+    sender.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_rx_batch_zero() {
+    use custom_utils::txrx::{AsyncBatchRecvExt, BatchRecvExt};
+    let (tx, rx) = channel::<u32>();
+    tx.send(0).unwrap();
+    let batch = rx.recv_batch(0, TIMEOUT).unwrap();
+    assert!(batch.items.is_empty() && !batch.closed);
+    assert_eq!(rx.recv(), Ok(0));
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<u32>(8);
+    tx.send(0).await.unwrap();
+    let batch = rx.recv_batch_async(0, TIMEOUT).await.unwrap();
+    assert!(batch.items.is_empty() && !batch.closed);
+    assert_eq!(rx.recv().await, Some(0));
+}
+
+fn bridge_send(tx: &custom_utils::txrx::BlockingSender<u32>, val: u32) -> Result<()> {
+    tx!(tx, val);
+    Ok(())
+}
+fn bridge_recv(rx: &custom_utils::txrx::BlockingReceiver<u32>) -> Result<u32> {
+    Ok(rx!(rx, timeout = TIMEOUT))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_bridge() {
+    let (tx, mut rx) = sync_to_async::<u32>(1);
+    let handle = std::thread::spawn(move || {
+        bridge_send(&tx, 1).unwrap();
+        bridge_send(&tx, 2).unwrap();
+    });
+    assert_eq!(rx.recv().await, Some(1));
+    assert_eq!(rx.recv().await, Some(2));
+    handle.join().unwrap();
+    assert_eq!(rx.recv().await, None);
+
+    let (tx, rx) = async_to_sync::<u32>(1);
+    let handle = std::thread::spawn(move || {
+        let first = bridge_recv(&rx).unwrap();
+        let err = bridge_recv(&rx).unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&ChannelError::Timeout(TIMEOUT)));
+        (first, rx)
+    });
+    async {
+        tx_async!(tx, 3);
+        Ok::<(), anyhow::Error>(())
+    }
+    .await
+    .unwrap();
+    let (first, rx) = tokio::task::spawn_blocking(move || handle.join().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(first, 3);
+    drop(tx);
+    let err = tokio::task::spawn_blocking(move || bridge_recv(&rx))
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&ChannelError::Closed));
+}
+
+#[tokio::test]
+async fn test_durable_queue() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("custom_utils_durable_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    {
+        let (tx, mut rx) = durable_queue::<String>(&dir)?;
+        tx!(tx, "a".to_string());
+        tx!(tx, "b".to_string());
+        tx!(tx, "c".to_string());
+        let entry = rx_async!(rx);
+        assert_eq!(entry.item, "a");
+        rx.ack(entry.seq)?;
+        let entry = rx_async!(rx);
+        assert_eq!(entry.item, "b");
+    }
+    // 模拟记录写到一半时崩溃，重启后截掉，之后写入的记录仍可读取
+    let log = dir.join(format!("{:020}.log", 0));
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&log)?
+        .write_all(&[3, 0, 0])?;
+    {
+        let (tx, _rx) = durable_queue::<String>(&dir)?;
+        tx!(tx, "d".to_string());
+    }
+    // b已取出但未确认，c、d未取出
+    let (tx, mut rx) = durable_queue::<String>(&dir)?;
+    assert_eq!(rx.unacked(), 3);
+    let b = rx_async!(rx);
+    let c = rx_async!(rx);
+    let d = rx_async!(rx);
+    assert_eq!(
+        (b.item.as_str(), c.item.as_str(), d.item.as_str()),
+        ("b", "c", "d")
+    );
+    rx.ack(b.seq)?;
+    rx.ack(c.seq)?;
+    rx.ack(d.seq)?;
+    assert_eq!(std::fs::metadata(&log)?.len(), 0);
+    drop(tx);
+    assert!(rx.recv().await.is_none());
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+fn segment_count(dir: &std::path::Path) -> usize {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .filter(|x| x.as_ref().unwrap().path().extension() == Some("log".as_ref()))
+        .count()
+}
+
+#[tokio::test]
+async fn test_durable_segments() -> Result<()> {
+    use custom_utils::txrx::durable_queue_with_segment_size;
+    let dir = std::env::temp_dir().join(format!("custom_utils_segments_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    {
+        // 每条记录14字节，5条后超过64字节开始新分段
+        let (tx, mut rx) = durable_queue_with_segment_size::<String>(&dir, 64)?;
+        for i in 0..10 {
+            tx.send(format!("m{}", i))?;
+        }
+        assert_eq!(segment_count(&dir), 3);
+        for seq in 0..8 {
+            assert_eq!(rx_async!(rx).seq, seq);
+            rx.ack(seq)?;
+        }
+        // 第一个分段的消息全部确认后被删除，第二个分段还有未确认的消息
+        assert_eq!(segment_count(&dir), 2);
+        assert_eq!(rx.unacked(), 2);
+    }
+    // 模拟确认记录写到一半时崩溃
+    let ack = dir.join(format!("{:020}.ack", 5));
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&ack)?
+        .write_all(&[1, 2, 3])?;
+    {
+        let (_tx, mut rx) = durable_queue_with_segment_size::<String>(&dir, 64)?;
+        assert_eq!(rx.unacked(), 2);
+        let entry = rx_async!(rx);
+        assert_eq!(entry.item, "m8");
+        rx.ack(entry.seq)?;
+    }
+    let (_tx, mut rx) = durable_queue_with_segment_size::<String>(&dir, 64)?;
+    assert_eq!(rx.unacked(), 1);
+    let entry = rx_async!(rx);
+    assert_eq!(entry.item, "m9");
+    rx.ack(entry.seq)?;
+    assert_eq!(segment_count(&dir), 1);
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+async fn watched_recv(watched_rx: &mut tokio::sync::mpsc::Receiver<u32>) -> Result<u32> {
+    Ok(rx_async!(watched_rx))
+}
+
+#[tokio::test]
+async fn test_watchdog() {
+    enable_watchdog(Duration::from_millis(20));
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<u32>(1);
+    let handle = tokio::spawn(async move { watched_recv(&mut rx).await });
+    tokio::time::sleep(TIMEOUT).await;
+    let stuck: Vec<_> = stuck_operations()
+        .into_iter()
+        .filter(|x| x.channel == "watched_rx")
+        .collect();
+    assert_eq!(stuck.len(), 1);
+    assert_eq!(stuck[0].kind, OpKind::Recv);
+    assert!(stuck[0].location.file().ends_with("util_txrx.rs"));
+    tx.send(1).await.unwrap();
+    assert_eq!(handle.await.unwrap().unwrap(), 1);
+    assert!(stuck_operations().iter().all(|x| x.channel != "watched_rx"));
+}
+
+fn jobs_send(jobs_tx: custom_utils::txrx::NamedSender<u32>) -> Result<()> {
+    tx!(jobs_tx, 1);
+    Ok(())
+}
+
+async fn jobs_send_async(jobs_tx: &custom_utils::txrx::NamedAsyncSender<u32>) -> Result<()> {
+    tx_async!(jobs_tx, 2);
+    Ok(())
+}
+
+/// 具名通道只以通道名登记一次
+#[tokio::test]
+async fn test_watchdog_named() {
+    enable_watchdog(Duration::from_millis(20));
+    let stuck_names = || -> Vec<String> {
+        stuck_operations()
+            .into_iter()
+            .map(|x| x.channel)
+            .filter(|x| x.starts_with("test_watch_jobs") || x == "jobs_tx")
+            .collect()
+    };
+    let (jobs_tx, jobs_rx) = channel_named::<u32>("test_watch_jobs", 0);
+    let handle = std::thread::spawn(move || jobs_send(jobs_tx));
+    tokio::time::sleep(TIMEOUT).await;
+    assert_eq!(stuck_names(), vec!["test_watch_jobs"]);
+    assert_eq!(jobs_rx.recv(), Ok(1));
+    handle.join().unwrap().unwrap();
+
+    let (jobs_tx, mut jobs_rx) = channel_named_async::<u32>("test_watch_jobs_async", 1);
+    jobs_tx.send(1).await.unwrap();
+    let handle = tokio::spawn(async move { jobs_send_async(&jobs_tx).await });
+    tokio::time::sleep(TIMEOUT).await;
+    assert_eq!(stuck_names(), vec!["test_watch_jobs_async"]);
+    assert_eq!(jobs_rx.recv().await, Some(1));
+    handle.await.unwrap().unwrap();
+    assert!(stuck_names().is_empty());
+}
+
+fn counted<T>(count: &std::cell::Cell<u32>, x: T) -> T {
+    count.set(count.get() + 1);
+    x
+}
+
+/// 宏只求值一次通道表达式
+#[tokio::test]
+async fn test_macro_eval_once() -> Result<()> {
+    let count = std::cell::Cell::new(0);
+    let (tx, rx) = channel::<u32>();
+    tx!(counted(&count, &tx), 1);
+    assert_eq!(rx!(counted(&count, &rx)), 1);
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<u32>(1);
+    tx_async!(counted(&count, &tx), 2);
+    assert_eq!(rx_async!(counted(&count, &mut rx)), 2);
+    tx_async!(counted(&count, &tx), 3, timeout = TIMEOUT);
+    assert_eq!(rx_async!(counted(&count, &mut rx), timeout = TIMEOUT), 3);
+    assert_eq!(count.get(), 6);
+    Ok(())
+}